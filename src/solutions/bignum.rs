@@ -0,0 +1,241 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    ops::{Add, Div, Mul, Sub},
+};
+
+// natural number that grows as needed, for answers past u128
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BigUint {
+    // base 2^64, least significant first, no trailing zeros
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn normalized(mut limbs: Vec<u64>) -> Self {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        Self { limbs }
+    }
+
+    fn bits(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => 64 * self.limbs.len() - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.limbs[i / 64] >> (i % 64) & 1 == 1
+    }
+
+    // self * 2 + bit
+    fn push_bit(&mut self, bit: bool) {
+        let mut carry = bit as u64;
+        for limb in self.limbs.iter_mut() {
+            let next = *limb >> 63;
+            *limb = *limb << 1 | carry;
+            carry = next;
+        }
+        if carry == 1 {
+            self.limbs.push(1);
+        }
+    }
+
+    // schoolbook long division, one bit at a time
+    fn div_rem(&self, rhs: &Self) -> (Self, Self) {
+        if rhs.limbs.is_empty() {
+            panic!("division by zero");
+        }
+        let mut quotient = vec![0; self.limbs.len()];
+        let mut rem = BigUint::default();
+        for i in (0..self.bits()).rev() {
+            rem.push_bit(self.bit(i));
+            if rem >= *rhs {
+                rem = &rem - rhs;
+                quotient[i / 64] |= 1 << (i % 64);
+            }
+        }
+        (Self::normalized(quotient), rem)
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(n: u128) -> Self {
+        Self::normalized(vec![n as u64, (n >> 64) as u64])
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        Self::from(n as u128)
+    }
+}
+
+impl From<u8> for BigUint {
+    fn from(n: u8) -> Self {
+        Self::from(n as u128)
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigUint {
+    type Output = BigUint;
+
+    fn add(self, rhs: Self) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len().max(rhs.limbs.len()) + 1);
+        let mut carry = false;
+        for k in 0..self.limbs.len().max(rhs.limbs.len()) {
+            let a = self.limbs.get(k).copied().unwrap_or(0);
+            let b = rhs.limbs.get(k).copied().unwrap_or(0);
+            let (sum, c1) = a.overflowing_add(b);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            limbs.push(sum);
+            carry = c1 || c2;
+        }
+        if carry {
+            limbs.push(1);
+        }
+        BigUint { limbs }
+    }
+}
+
+impl Sub for &BigUint {
+    type Output = BigUint;
+
+    fn sub(self, rhs: Self) -> BigUint {
+        if *self < *rhs {
+            panic!("attempt to subtract with overflow");
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = false;
+        for (k, &a) in self.limbs.iter().enumerate() {
+            let b = rhs.limbs.get(k).copied().unwrap_or(0);
+            let (diff, b1) = a.overflowing_sub(b);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            limbs.push(diff);
+            borrow = b1 || b2;
+        }
+        BigUint::normalized(limbs)
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, rhs: Self) -> BigUint {
+        let mut limbs = vec![0u64; self.limbs.len() + rhs.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in rhs.limbs.iter().enumerate() {
+                let cur = limbs[i + j] as u128 + a as u128 * b as u128 + carry;
+                limbs[i + j] = cur as u64;
+                carry = cur >> 64;
+            }
+            limbs[i + rhs.limbs.len()] = carry as u64;
+        }
+        BigUint::normalized(limbs)
+    }
+}
+
+impl Div for &BigUint {
+    type Output = BigUint;
+
+    fn div(self, rhs: Self) -> BigUint {
+        self.div_rem(rhs).0
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+        if self.limbs.is_empty() {
+            return write!(f, "0");
+        }
+        // peel off 19 decimal digits at a time, most significant limb first
+        let mut limbs = self.limbs.clone();
+        let mut chunks = vec![];
+        while !limbs.is_empty() {
+            let mut rem = 0u128;
+            for limb in limbs.iter_mut().rev() {
+                let cur = (rem << 64) | *limb as u128;
+                *limb = (cur / CHUNK) as u64;
+                rem = cur % CHUNK;
+            }
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+            chunks.push(rem);
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{chunk:019}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod bignum_tests {
+    use super::*;
+
+    #[test]
+    fn test_against_u128() {
+        let values: [u128; 6] = [
+            0,
+            1,
+            u64::MAX as u128,
+            u64::MAX as u128 + 1,
+            340_282_366_920_938_463_463_374_607_431,
+            u128::MAX / 3,
+        ];
+        for &a in values.iter() {
+            for &b in values.iter() {
+                let (x, y) = (BigUint::from(a), BigUint::from(b));
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+                if let Some(sum) = a.checked_add(b) {
+                    assert_eq!(&x + &y, BigUint::from(sum));
+                }
+                if a >= b {
+                    assert_eq!(&x - &y, BigUint::from(a - b));
+                }
+                if let Some(product) = a.checked_mul(b) {
+                    assert_eq!(&x * &y, BigUint::from(product));
+                }
+                if let Some(quotient) = a.checked_div(b) {
+                    assert_eq!(&x / &y, BigUint::from(quotient));
+                }
+                assert_eq!(x.to_string(), a.to_string());
+            }
+        }
+    }
+
+    #[test]
+    fn test_past_u128() {
+        let max = BigUint::from(u128::MAX);
+        let square = &max * &max;
+        // (2^128 - 1)^2 = 2^256 - 2^129 + 1
+        assert_eq!(
+            square.to_string(),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
+        assert_eq!(&square / &max, max);
+        assert_eq!(&(&square - &max) / &max, &max - &BigUint::from(1u8));
+        assert!(square > max);
+    }
+}
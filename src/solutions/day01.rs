@@ -13,45 +13,112 @@ treb7uchet",
     }
 
     fn solve_part_1(input: String) -> String {
+        let table = WordTable::numerals();
         input
             .lines()
-            .map(|line| get_calibrated_digit(line))
+            .map(|line| table.calibration(line).unwrap())
             .sum::<usize>()
             .to_string()
     }
 
     fn solve_part_2(input: String) -> String {
+        let table = WordTable::english();
         input
             .lines()
-            .map(|line| translate_line(line))
-            .map(|line| get_calibrated_digit(&line))
+            .map(|line| table.calibration(line).unwrap())
             .sum::<usize>()
             .to_string()
     }
 }
 
-fn get_calibrated_digit(line: &str) -> usize {
-    let digits: Vec<usize> = line
-        .chars()
-        .filter_map(|c| c.to_digit(10))
-        .map(|n| n as usize)
-        .collect();
-    10 * digits.first().unwrap() + digits.last().unwrap()
+// a digit found in a line, at byte offset `pos`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Token {
+    pos: usize,
+    len: usize,
+    digit: usize,
 }
 
-fn translate_line(line: &str) -> String {
-    let mut line = line.to_string();
-    line = line.replace("one", "o1e");
-    line = line.replace("two", "t2o");
-    line = line.replace("three", "t3e");
-    line = line.replace("four", "f4r");
-    line = line.replace("five", "f5e");
-    line = line.replace("six", "s6x");
-    line = line.replace("seven", "s7n");
-    line = line.replace("eight", "e8t");
-    line = line.replace("nine", "n9e");
-
-    line
+// spelled-out digits; numerals are always recognized
+struct WordTable {
+    words: Vec<(String, usize)>,
+}
+
+impl WordTable {
+    fn numerals() -> Self {
+        Self { words: vec![] }
+    }
+
+    fn english() -> Self {
+        [
+            "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+        ]
+        .iter()
+        .enumerate()
+        .fold(Self::numerals(), |table, (i, word)| table.with(word, i + 1))
+    }
+
+    fn with(mut self, word: &str, digit: usize) -> Self {
+        self.words.push((word.to_string(), digit));
+        self
+    }
+
+    // the longest token that starts at `pos`
+    fn token_at(&self, line: &[u8], pos: usize) -> Option<Token> {
+        if line[pos].is_ascii_digit() {
+            return Some(Token {
+                pos,
+                len: 1,
+                digit: (line[pos] - b'0') as usize,
+            });
+        }
+        self.words
+            .iter()
+            .filter(|(word, _)| line[pos..].starts_with(word.as_bytes()))
+            .max_by_key(|(word, _)| word.len())
+            .map(|(word, digit)| Token {
+                pos,
+                len: word.len(),
+                digit: *digit,
+            })
+    }
+
+    // the longest token that ends right before `end`
+    fn token_before(&self, line: &[u8], end: usize) -> Option<Token> {
+        if line[end - 1].is_ascii_digit() {
+            return Some(Token {
+                pos: end - 1,
+                len: 1,
+                digit: (line[end - 1] - b'0') as usize,
+            });
+        }
+        self.words
+            .iter()
+            .filter(|(word, _)| line[..end].ends_with(word.as_bytes()))
+            .max_by_key(|(word, _)| word.len())
+            .map(|(word, digit)| Token {
+                pos: end - word.len(),
+                len: word.len(),
+                digit: *digit,
+            })
+    }
+
+    // tokens may overlap, as in "eightwo", so each end is scanned on its own
+    fn first(&self, line: &str) -> Option<Token> {
+        let line = line.as_bytes();
+        (0..line.len()).find_map(|pos| self.token_at(line, pos))
+    }
+
+    fn last(&self, line: &str) -> Option<Token> {
+        let line = line.as_bytes();
+        (1..=line.len())
+            .rev()
+            .find_map(|end| self.token_before(line, end))
+    }
+
+    fn calibration(&self, line: &str) -> Option<usize> {
+        Some(10 * self.first(line)?.digit + self.last(line)?.digit)
+    }
 }
 
 #[cfg(test)]
@@ -79,4 +146,51 @@ mod day01_tests {
         let ans = Day01::solve_part_2(input);
         assert_eq!(ans, "281");
     }
+
+    #[test]
+    fn test_overlapping_tokens() {
+        let table = WordTable::english();
+        assert_eq!(
+            table.first("eightwo"),
+            Some(Token {
+                pos: 0,
+                len: 5,
+                digit: 8
+            })
+        );
+        assert_eq!(
+            table.last("eightwo"),
+            Some(Token {
+                pos: 4,
+                len: 3,
+                digit: 2
+            })
+        );
+        assert_eq!(table.calibration("xtwone3four"), Some(24));
+        assert_eq!(table.calibration("abc"), None);
+        assert_eq!(WordTable::numerals().calibration("one2three"), Some(22));
+    }
+
+    #[test]
+    fn test_custom_words() {
+        let table = WordTable::english().with("zero", 0).with("uno", 1);
+        assert_eq!(table.calibration("zerotwouno"), Some(1));
+        assert_eq!(table.calibration("xzero"), Some(0));
+
+        let korean = ["일", "이", "삼", "사", "오", "육", "칠", "팔", "구"]
+            .iter()
+            .enumerate()
+            .fold(WordTable::numerals(), |table, (i, word)| {
+                table.with(word, i + 1)
+            });
+        assert_eq!(korean.calibration("하나둘삼x7구"), Some(39));
+        assert_eq!(
+            korean.last("칠a"),
+            Some(Token {
+                pos: 0,
+                len: 3,
+                digit: 7
+            })
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use super::Solution;
 
 pub struct Day02;
@@ -14,119 +16,140 @@ impl Solution for Day02 {
     }
 
     fn solve_part_1(input: String) -> String {
-        let predicate = Game {
-            id: usize::MAX,
-            red: 12,
-            green: 13,
-            blue: 14,
-        };
-        input
-            .lines()
-            .map(|line| line.parse::<Game>().unwrap())
-            .filter_map(|game| {
-                if game.red <= predicate.red
-                    && game.green <= predicate.green
-                    && game.blue <= predicate.blue
-                {
-                    Some(game.id)
-                } else {
-                    None
-                }
-            })
+        let games = parse_games(&input, &RGB).unwrap();
+        let bag: Bag = HashMap::from([
+            ("red".to_string(), 12),
+            ("green".to_string(), 13),
+            ("blue".to_string(), 14),
+        ]);
+        possible_games(&games, &bag)
+            .iter()
             .sum::<usize>()
             .to_string()
     }
 
     fn solve_part_2(input: String) -> String {
-        input
-            .lines()
-            .map(|line| line.parse::<Game>().unwrap())
-            .map(|game| game.get_power())
+        parse_games(&input, &RGB)
+            .unwrap()
+            .iter()
+            .map(|game| game.get_power(&RGB))
             .sum::<usize>()
             .to_string()
     }
 }
 
+const RGB: [&str; 3] = ["red", "green", "blue"];
+
+// number of cubes of each colour
+type Bag = HashMap<String, usize>;
+
+#[derive(Debug, PartialEq, Eq)]
+enum GameError {
+    // not "Game <id>: <draws>"
+    MalformedGame(String),
+    // not "<count> <colour>, ...", or a colour given twice
+    MalformedDraw(String),
+    UnknownColour(String),
+}
+
+// every game, checking that only the colours in `palette` show up
+fn parse_games(input: &str, palette: &[&str]) -> Result<Vec<Game>, GameError> {
+    input
+        .lines()
+        .map(|line| {
+            let game: Game = line.parse()?;
+            for draw in game.draws.iter() {
+                if let Some(colour) = draw.balls.keys().find(|c| !palette.contains(&c.as_str())) {
+                    return Err(GameError::UnknownColour(colour.clone()));
+                }
+            }
+            Ok(game)
+        })
+        .collect()
+}
+
+// ids of the games that could have been played with `bag`
+fn possible_games(games: &[Game], bag: &Bag) -> Vec<usize> {
+    games
+        .iter()
+        .filter(|game| game.is_possible_with(bag))
+        .map(|game| game.id)
+        .collect()
+}
+
 struct Game {
     id: usize,
-    red: usize,
-    green: usize,
-    blue: usize,
+    draws: Vec<Draw>,
 }
 
 impl Game {
-    fn get_power(&self) -> usize {
-        self.red * self.green * self.blue
+    fn is_possible_with(&self, bag: &Bag) -> bool {
+        self.draws.iter().all(|draw| {
+            draw.balls
+                .iter()
+                .all(|(colour, num)| bag.get(colour).is_some_and(|n| num <= n))
+        })
+    }
+
+    fn minimum_bag(&self) -> Bag {
+        let mut bag = Bag::new();
+        for draw in self.draws.iter() {
+            for (colour, num) in draw.balls.iter() {
+                let n = bag.entry(colour.clone()).or_insert(0);
+                *n = (*n).max(*num);
+            }
+        }
+        bag
+    }
+
+    // colours that never show up count as zero
+    fn get_power(&self, colours: &[&str]) -> usize {
+        let bag = self.minimum_bag();
+        colours
+            .iter()
+            .map(|colour| bag.get(*colour).copied().unwrap_or(0))
+            .product()
     }
 }
 
 impl std::str::FromStr for Game {
-    type Err = ();
+    type Err = GameError;
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let mut colon = line.split(":");
-        let part_game = colon.next().unwrap();
-        let game_id: usize = part_game
-            .split_whitespace()
-            .skip(1)
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let part_draw = colon.next().unwrap();
-        let draws: Vec<Draw> = part_draw
-            .split(";")
-            .map(|word| word.trim().parse().unwrap())
-            .collect();
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-        for draw in draws {
-            red = red.max(draw.red);
-            green = green.max(draw.green);
-            blue = blue.max(draw.blue);
-        }
-        Ok(Self {
-            id: game_id,
-            red,
-            green,
-            blue,
-        })
+        let error = || GameError::MalformedGame(line.trim().to_string());
+        let (part_game, part_draw) = line.split_once(':').ok_or_else(error)?;
+        let id = match part_game.split_whitespace().collect::<Vec<&str>>()[..] {
+            ["Game", id] => id.parse().map_err(|_| error())?,
+            _ => return Err(error()),
+        };
+        let draws = part_draw
+            .split(';')
+            .map(|word| word.trim().parse())
+            .collect::<Result<Vec<Draw>, GameError>>()?;
+        Ok(Self { id, draws })
     }
 }
 
 struct Draw {
-    red: usize,
-    green: usize,
-    blue: usize,
+    balls: HashMap<String, usize>,
 }
 
 impl std::str::FromStr for Draw {
-    type Err = ();
+    type Err = GameError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let balls = s.split(",");
-        let mut red = 0;
-        let mut green = 0;
-        let mut blue = 0;
-        for ball in balls {
-            let mut words = ball.trim().split_whitespace();
-            let num: usize = words.next().unwrap().parse().unwrap();
-            let color = words.next().unwrap().trim();
-            match color {
-                "red" => {
-                    red = num;
-                }
-                "blue" => {
-                    blue = num;
-                }
-                "green" => {
-                    green = num;
-                }
-                _ => unreachable!(),
+        let error = || GameError::MalformedDraw(s.to_string());
+        let mut balls = HashMap::new();
+        for ball in s.split(',') {
+            let (num, colour) = match ball.split_whitespace().collect::<Vec<&str>>()[..] {
+                [num, colour] => (num.parse().map_err(|_| error())?, colour),
+                _ => return Err(error()),
+            };
+            if balls.insert(colour.to_string(), num).is_some() {
+                return Err(error());
             }
         }
-        Ok(Self { red, green, blue })
+        Ok(Self { balls })
     }
 }
 
@@ -147,4 +170,55 @@ mod day02_tests {
         let ans = Day02::solve_part_2(input);
         assert_eq!(ans, "2286");
     }
+
+    #[test]
+    fn test_queries() {
+        let games = parse_games(&Day02::test_input(), &RGB).unwrap();
+        let bag: Bag = HashMap::from([
+            ("red".to_string(), 20),
+            ("green".to_string(), 13),
+            ("blue".to_string(), 6),
+        ]);
+        assert_eq!(possible_games(&games, &bag), vec![1, 2, 3, 5]);
+
+        let minimum = games[2].minimum_bag();
+        assert_eq!(minimum.get("red"), Some(&20));
+        assert_eq!(minimum.get("green"), Some(&13));
+        assert_eq!(minimum.get("blue"), Some(&6));
+        assert_eq!(games[2].get_power(&RGB), 1560);
+    }
+
+    #[test]
+    fn test_other_colours() {
+        let palette = ["red", "cyan"];
+        let games = parse_games("Game 7: 2 cyan, 1 red; 5 cyan", &palette).unwrap();
+        assert_eq!(games[0].get_power(&palette), 5);
+        // no blue cubes at all
+        assert_eq!(games[0].get_power(&RGB), 0);
+
+        let bag: Bag = HashMap::from([("cyan".to_string(), 5), ("red".to_string(), 1)]);
+        assert_eq!(possible_games(&games, &bag), vec![7]);
+        let bag: Bag = HashMap::from([("cyan".to_string(), 5)]);
+        assert_eq!(possible_games(&games, &bag), vec![]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse_games("Game 1: 2 cyan", &RGB).err(),
+            Some(GameError::UnknownColour("cyan".to_string()))
+        );
+        assert_eq!(
+            parse_games("Game 1: 2 red, blue", &RGB).err(),
+            Some(GameError::MalformedDraw("2 red, blue".to_string()))
+        );
+        assert_eq!(
+            parse_games("Game 1: 2 red, 3 red", &RGB).err(),
+            Some(GameError::MalformedDraw("2 red, 3 red".to_string()))
+        );
+        assert_eq!(
+            parse_games("Game one: 2 red", &RGB).err(),
+            Some(GameError::MalformedGame("Game one: 2 red".to_string()))
+        );
+    }
 }
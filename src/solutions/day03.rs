@@ -27,33 +27,30 @@ impl Solution for Day03 {
 
     fn solve_part_2(input: String) -> String {
         let schematic: EngineSchematic = input.parse().unwrap();
-        let mut gears: HashMap<Pos, Vec<usize>> = HashMap::new();
-        for part in schematic.symbols.iter() {
-            if *part.1 == '*' {
-                gears.insert(*part.0, vec![]);
-            }
-        }
-        for number in schematic.numbers.iter() {
-            for adj in number.adjacent_positions(schematic.size) {
-                if let Some(vec) = gears.get_mut(&adj) {
-                    vec.push(number.value);
-                }
-            }
-        }
-        gears
-            .values()
-            .filter_map(|vec| {
-                if vec.len() == 2 {
-                    Some(vec[0] * vec[1])
-                } else {
-                    None
-                }
-            })
+        schematic
+            .gear_values('*', 2, Reducer::Product)
+            .iter()
             .sum::<usize>()
             .to_string()
     }
 }
 
+// how the numbers around a gear are combined
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Reducer {
+    Product,
+    Sum,
+}
+
+impl Reducer {
+    fn reduce(&self, values: &[usize]) -> usize {
+        match self {
+            Reducer::Product => values.iter().product(),
+            Reducer::Sum => values.iter().sum(),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Hash, PartialEq, Eq, Debug)]
 struct Pos(usize, usize);
 
@@ -67,21 +64,21 @@ struct EngineSchematic {
 struct Number {
     value: usize,
     pos: Pos,
+    // number of digits, leading zeros included
+    len: usize,
 }
 
-fn num_len(mut num: usize) -> usize {
-    let mut ans = 0;
-    while num > 0 {
-        ans += 1;
-        num /= 10;
-    }
-    ans
+// a character that is neither a digit, '.', nor a symbol
+#[derive(Debug, PartialEq, Eq)]
+struct SchematicError {
+    pos: Pos,
+    found: char,
 }
 
 impl Number {
     fn adjacent_positions(&self, clamp: Pos) -> Vec<Pos> {
         let mut ans = vec![];
-        let len = num_len(self.value);
+        let len = self.len;
 
         let c_start = 1.max(self.pos.1) - 1;
         let c_end = (self.pos.1 + len + 1).min(clamp.1);
@@ -98,7 +95,7 @@ impl Number {
         if self.pos.1 + len < clamp.1 {
             ans.push(Pos(self.pos.0, self.pos.1 + len));
         }
-        if self.pos.0 <= clamp.0 - 2 {
+        if self.pos.0 + 1 < clamp.0 {
             let r = self.pos.0 + 1;
             for c in c_start..c_end {
                 ans.push(Pos(r, c));
@@ -110,6 +107,47 @@ impl Number {
 }
 
 impl EngineSchematic {
+    fn numbers_adjacent_to(&self, pos: Pos) -> Vec<&Number> {
+        self.numbers
+            .iter()
+            .filter(|num| num.adjacent_positions(self.size).contains(&pos))
+            .collect()
+    }
+
+    fn symbols_adjacent_to(&self, num: &Number) -> Vec<(Pos, char)> {
+        num.adjacent_positions(self.size)
+            .into_iter()
+            .filter_map(|pos| self.symbols.get(&pos).map(|symbol| (pos, *symbol)))
+            .collect()
+    }
+
+    // symbols of `kind` with exactly `k` adjacent numbers, sorted by position
+    fn gears(&self, kind: char, k: usize) -> Vec<(Pos, Vec<usize>)> {
+        let mut gears: Vec<(Pos, Vec<usize>)> = self
+            .symbols
+            .iter()
+            .filter(|(_, symbol)| **symbol == kind)
+            .map(|(pos, _)| {
+                let values = self
+                    .numbers_adjacent_to(*pos)
+                    .iter()
+                    .map(|num| num.value)
+                    .collect();
+                (*pos, values)
+            })
+            .filter(|(_, values): &(Pos, Vec<usize>)| values.len() == k)
+            .collect();
+        gears.sort_by_key(|(pos, _)| (pos.0, pos.1));
+        gears
+    }
+
+    fn gear_values(&self, kind: char, k: usize, reducer: Reducer) -> Vec<usize> {
+        self.gears(kind, k)
+            .iter()
+            .map(|(_, values)| reducer.reduce(values))
+            .collect()
+    }
+
     fn valid_numbers(&self) -> Vec<usize> {
         self.numbers
             .iter()
@@ -126,48 +164,46 @@ impl EngineSchematic {
 }
 
 impl std::str::FromStr for EngineSchematic {
-    type Err = ();
+    type Err = SchematicError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let chars: Vec<Vec<char>> = s
             .lines()
             .map(|line| line.trim().chars().collect())
             .collect();
-        let size: Pos = Pos(chars.len(), chars[1].len());
+        // rows may be ragged: the widest one decides the width
+        let width = chars.iter().map(|row| row.len()).max().unwrap_or(0);
+        let size: Pos = Pos(chars.len(), width);
         let mut numbers = vec![];
         let mut symbols = HashMap::new();
-        let mut num = 0;
         for (r, row) in chars.iter().enumerate() {
-            let max_c = row.len();
             let mut c = 0;
-            loop {
-                let ch = chars[r][c];
-                if ch.is_digit(10) {
-                    let mut ind = c;
-                    loop {
-                        let lookahead = ind + 1;
-                        let now = chars[r][ind].to_digit(10).unwrap() as usize;
-                        num = num * 10 + now;
-                        if lookahead >= size.0 || !chars[r][lookahead].is_digit(10) {
-                            numbers.push(Number {
-                                value: num,
-                                pos: Pos(r, c),
-                            });
-                            num = 0;
-                            break;
-                        }
-
-                        ind += 1;
+            while c < row.len() {
+                let ch = row[c];
+                if let Some(digit) = ch.to_digit(10) {
+                    let start = c;
+                    let mut value = digit as usize;
+                    c += 1;
+                    while let Some(digit) = row.get(c).and_then(|ch| ch.to_digit(10)) {
+                        value = value * 10 + digit as usize;
+                        c += 1;
                     }
-                    c = ind;
+                    numbers.push(Number {
+                        value,
+                        pos: Pos(r, start),
+                        len: c - start,
+                    });
+                    continue;
                 } else if ch == '.' {
-                } else {
+                } else if ch.is_ascii_punctuation() {
                     symbols.insert(Pos(r, c), ch);
+                } else {
+                    return Err(SchematicError {
+                        pos: Pos(r, c),
+                        found: ch,
+                    });
                 }
                 c += 1;
-                if c >= max_c {
-                    break;
-                }
             }
         }
 
@@ -196,4 +232,84 @@ mod day03_tests {
         let ans = Day03::solve_part_2(input);
         assert_eq!(ans, "467835");
     }
+
+    #[test]
+    fn test_queries() {
+        let schematic: EngineSchematic = Day03::test_input().parse().unwrap();
+
+        let values: Vec<usize> = schematic
+            .numbers_adjacent_to(Pos(1, 3))
+            .iter()
+            .map(|num| num.value)
+            .collect();
+        assert_eq!(values, vec![467, 35]);
+
+        let seven_five_five = schematic.numbers.iter().find(|n| n.value == 755).unwrap();
+        assert_eq!(
+            schematic.symbols_adjacent_to(seven_five_five),
+            vec![(Pos(8, 5), '*')]
+        );
+        let one_one_four = schematic.numbers.iter().find(|n| n.value == 114).unwrap();
+        assert_eq!(schematic.symbols_adjacent_to(one_one_four), vec![]);
+
+        assert_eq!(schematic.gears('*', 1), vec![(Pos(4, 3), vec![617])]);
+        assert_eq!(
+            schematic.gear_values('*', 2, Reducer::Sum),
+            vec![467 + 35, 755 + 598]
+        );
+        assert_eq!(schematic.gear_values('#', 1, Reducer::Product), vec![633]);
+    }
+
+    fn values(input: &str) -> Vec<usize> {
+        let schematic: EngineSchematic = input.parse().unwrap();
+        schematic.valid_numbers()
+    }
+
+    #[test]
+    fn test_single_row() {
+        assert_eq!(values("12*3..4"), vec![12, 3]);
+        assert_eq!(values("007#"), vec![7]);
+        assert_eq!(values("0"), vec![]);
+        assert_eq!(values(""), vec![]);
+    }
+
+    #[test]
+    fn test_rectangular() {
+        // wider than tall, numbers touching the right edge
+        let input = "......*.12
+        123......%
+        ..........";
+        assert_eq!(values(input), vec![12]);
+
+        // taller than wide
+        let input = "1.
+        .+
+        .3
+        4.";
+        assert_eq!(values(input), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_ragged() {
+        let input = "..*
+        ....45
+        7
+        .$..9";
+        assert_eq!(values(input), vec![7]);
+        let schematic: EngineSchematic = input.parse().unwrap();
+        assert_eq!(schematic.size, Pos(4, 6));
+    }
+
+    #[test]
+    fn test_invalid_character() {
+        let input = "467..114..
+        ...*..a...";
+        assert_eq!(
+            input.parse::<EngineSchematic>().unwrap_err(),
+            SchematicError {
+                pos: Pos(1, 6),
+                found: 'a'
+            }
+        );
+    }
 }
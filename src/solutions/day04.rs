@@ -30,18 +30,63 @@ impl Solution for Day04 {
             .lines()
             .map(|line| line.trim().parse().unwrap())
             .collect();
-        let mut counts: Vec<usize> = cards.iter().map(|_| 1).collect();
-
-        // 1, 3
-        // => 2, 3, 4
-        for i in 0..cards.len() {
-            let matches = cards[i].num_matches();
-            for j in 0..matches {
-                counts[i + j + 1] += counts[i];
-            }
+        cascade(&cards, Overflow::Error)
+            .unwrap()
+            .total()
+            .to_string()
+    }
+}
+
+// what to do when a card wins copies of cards past the end of the table
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Overflow {
+    Clamp,
+    Error,
+}
+
+// card `card` (0-based) has `matches` matches, but only `remaining` cards follow it
+#[derive(Debug, PartialEq, Eq)]
+struct CascadeOverflow {
+    card: usize,
+    matches: usize,
+    remaining: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Cascade {
+    counts: Vec<usize>,
+    // for each card, the earlier cards that won copies of it, and how many
+    sources: Vec<Vec<(usize, usize)>>,
+}
+
+impl Cascade {
+    fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+fn cascade(cards: &[ScratchCard], overflow: Overflow) -> Result<Cascade, CascadeOverflow> {
+    let mut counts: Vec<usize> = cards.iter().map(|_| 1).collect();
+    let mut sources: Vec<Vec<(usize, usize)>> = cards.iter().map(|_| vec![]).collect();
+
+    // 1, 3
+    // => 2, 3, 4
+    for (i, card) in cards.iter().enumerate() {
+        let matches = card.num_matches();
+        let remaining = cards.len() - i - 1;
+        if matches > remaining && overflow == Overflow::Error {
+            return Err(CascadeOverflow {
+                card: i,
+                matches,
+                remaining,
+            });
+        }
+        for j in (i + 1)..=(i + matches.min(remaining)) {
+            counts[j] += counts[i];
+            sources[j].push((i, counts[i]));
         }
-        counts.iter().sum::<usize>().to_string()
     }
+    Ok(Cascade { counts, sources })
 }
 
 struct ScratchCard {
@@ -118,4 +163,37 @@ mod day04_tests {
         let ans = Day04::solve_part_2(input);
         assert_eq!(ans, "30");
     }
+
+    #[test]
+    fn test_cascade() {
+        let cards: Vec<ScratchCard> = Day04::test_input()
+            .lines()
+            .map(|line| line.trim().parse().unwrap())
+            .collect();
+        let cascade = cascade(&cards, Overflow::Error).unwrap();
+        assert_eq!(cascade.counts, vec![1, 2, 4, 8, 14, 1]);
+        // card 4 has one original, and copies won by cards 1, 2 and 3
+        assert_eq!(cascade.sources[3], vec![(0, 1), (1, 2), (2, 4)]);
+        assert_eq!(cascade.sources[5], vec![]);
+    }
+
+    #[test]
+    fn test_cascade_overflow() {
+        let cards: Vec<ScratchCard> = "Card 1: 1 2 | 1 2
+        Card 2: 3 4 5 | 3 4 5"
+            .lines()
+            .map(|line| line.trim().parse().unwrap())
+            .collect();
+        assert_eq!(
+            cascade(&cards, Overflow::Error),
+            Err(CascadeOverflow {
+                card: 0,
+                matches: 2,
+                remaining: 1,
+            })
+        );
+        let clamped = cascade(&cards, Overflow::Clamp).unwrap();
+        assert_eq!(clamped.counts, vec![1, 2]);
+        assert_eq!(clamped.total(), 3);
+    }
 }
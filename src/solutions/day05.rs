@@ -1,3 +1,5 @@
+use std::collections::{HashMap, VecDeque};
+
 use super::Solution;
 
 pub struct Day05;
@@ -50,17 +52,12 @@ humidity-to-location map:
             .skip(1)
             .map(|w| w.trim().parse().unwrap())
             .collect();
-        let almanacs: Vec<Almanac> = (0..7).map(|_| Almanac::from(&mut lines)).collect();
+        let almanacs = parse_almanacs(&mut lines).unwrap();
+        let map = compose_chain(&almanacs).unwrap();
 
         seeds
             .iter()
-            .map(|seed| {
-                let mut s = *seed;
-                for almanac in almanacs.iter() {
-                    s = almanac.apply(s);
-                }
-                s
-            })
+            .map(|seed| map.apply(*seed))
             .min()
             .unwrap()
             .to_string()
@@ -76,12 +73,307 @@ humidity-to-location map:
             seeds.push(Range(start, start + range));
         }
 
-        let almanacs: Vec<Almanac> = (0..7).map(|_| Almanac::from(&mut lines)).collect();
+        let almanacs = parse_almanacs(&mut lines).unwrap();
+        let map = compose_chain(&almanacs).unwrap();
+
+        seeds
+            .iter()
+            .filter_map(|range| map.min_in(range))
+            .min()
+            .unwrap()
+            .to_string()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ChainError {
+    // a header that does not look like "X-to-Y map:"
+    Header(String),
+    // a mapping line that is not three numbers
+    Mapping(String),
+    // an almanac starts from a different category than the previous one ended
+    Broken { expected: String, found: String },
+    // the chain does not end at "location"
+    Incomplete { last: String },
+    // no almanac mentions the category
+    Unknown(String),
+    // no chain of almanacs, in either direction, joins the two categories
+    Disconnected { from: String, to: String },
+}
+
+fn parse_header(line: &str) -> Result<(String, String), ChainError> {
+    let error = || ChainError::Header(line.to_string());
+    let names = line.trim().strip_suffix(" map:").ok_or_else(error)?;
+    let (source, destination) = names.split_once("-to-").ok_or_else(error)?;
+    if source.is_empty() || destination.is_empty() {
+        return Err(error());
+    }
+    Ok((source.to_string(), destination.to_string()))
+}
 
-        for almanac in almanacs {
-            seeds = almanac.apply_to(seeds);
+fn parse_almanacs(lines: &mut std::str::Lines) -> Result<Vec<Almanac>, ChainError> {
+    let mut almanacs = vec![];
+    while let Some(line) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
         }
-        seeds.iter().map(|rng| rng.0).min().unwrap().to_string()
+        almanacs.push(Almanac::from(line, lines)?);
+    }
+    Ok(almanacs)
+}
+
+// folds seed -> soil -> ... -> location into one map
+fn compose_chain(almanacs: &[Almanac]) -> Result<PiecewiseMap, ChainError> {
+    let mut expected = "seed";
+    let mut map = PiecewiseMap::identity();
+    for almanac in almanacs.iter() {
+        if almanac.source != expected {
+            return Err(ChainError::Broken {
+                expected: expected.to_string(),
+                found: almanac.source.clone(),
+            });
+        }
+        map = map.then(&almanac.to_piecewise());
+        expected = &almanac.destination;
+    }
+    if expected != "location" {
+        return Err(ChainError::Incomplete {
+            last: expected.to_string(),
+        });
+    }
+    Ok(map)
+}
+
+// categories are nodes and almanacs are edges, which can be walked backwards as well
+struct Categories {
+    almanacs: Vec<Almanac>,
+    maps: Vec<PiecewiseMap>,
+}
+
+impl Categories {
+    fn from(almanacs: Vec<Almanac>) -> Self {
+        let maps = almanacs
+            .iter()
+            .map(|almanac| almanac.to_piecewise())
+            .collect();
+        Self { almanacs, maps }
+    }
+
+    fn knows(&self, category: &str) -> bool {
+        self.almanacs
+            .iter()
+            .any(|almanac| almanac.source == category || almanac.destination == category)
+    }
+
+    // the almanacs to apply in order, and whether each is applied forward
+    fn path(&self, from: &str, to: &str) -> Result<Vec<(usize, bool)>, ChainError> {
+        for category in [from, to] {
+            if !self.knows(category) {
+                return Err(ChainError::Unknown(category.to_string()));
+            }
+        }
+        let mut previous: HashMap<&str, (&str, usize, bool)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(category) = queue.pop_front() {
+            if category == to {
+                break;
+            }
+            for (i, almanac) in self.almanacs.iter().enumerate() {
+                let (next, forward) = if almanac.source == category {
+                    (almanac.destination.as_str(), true)
+                } else if almanac.destination == category {
+                    (almanac.source.as_str(), false)
+                } else {
+                    continue;
+                };
+                if next != from && !previous.contains_key(next) {
+                    previous.insert(next, (category, i, forward));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut path = vec![];
+        let mut category = to;
+        while category != from {
+            let Some(&(before, i, forward)) = previous.get(category) else {
+                return Err(ChainError::Disconnected {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                });
+            };
+            path.push((i, forward));
+            category = before;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    fn convert(&self, ranges: &[Range], from: &str, to: &str) -> Result<Vec<Range>, ChainError> {
+        let mut ranges = normalized(ranges.to_vec());
+        for (i, forward) in self.path(from, to)? {
+            ranges = if forward {
+                self.maps[i].image_of(&ranges)
+            } else {
+                self.maps[i].preimage_of(&ranges)
+            };
+        }
+        Ok(ranges)
+    }
+
+    // going backwards, a value can come from more than one place
+    fn convert_value(&self, value: usize, from: &str, to: &str) -> Result<Vec<usize>, ChainError> {
+        let ranges = self.convert(&[Range(value, value + 1)], from, to)?;
+        Ok(ranges.iter().flat_map(|range| range.0..range.1).collect())
+    }
+}
+
+// sorted, without empty or overlapping ranges
+fn normalized(mut ranges: Vec<Range>) -> Vec<Range> {
+    ranges.retain(|range| range.0 < range.1);
+    ranges.sort_by_key(|range| range.0);
+    let mut merged: Vec<Range> = vec![];
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.0 <= last.1 => last.1 = last.1.max(range.1),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+// x in start..end maps to x + offset
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct Piece {
+    start: usize,
+    end: usize,
+    offset: i64,
+}
+
+impl Piece {
+    fn image(&self) -> Range {
+        Range(
+            shifted(self.start, self.offset),
+            shifted(self.end, self.offset),
+        )
+    }
+}
+
+// x + offset in i128, clamped so the open-ended last piece stays at usize::MAX
+fn shifted(x: usize, offset: i64) -> usize {
+    (x as i128 + offset as i128).clamp(0, usize::MAX as i128) as usize
+}
+
+// sorted pieces covering 0..usize::MAX without gaps
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
+
+impl PiecewiseMap {
+    fn identity() -> Self {
+        Self {
+            pieces: vec![Piece {
+                start: 0,
+                end: usize::MAX,
+                offset: 0,
+            }],
+        }
+    }
+
+    fn piece_at(&self, x: usize) -> &Piece {
+        let i = self.pieces.partition_point(|piece| piece.end <= x);
+        &self.pieces[i]
+    }
+
+    fn apply(&self, x: usize) -> usize {
+        shifted(x, self.piece_at(x).offset)
+    }
+
+    // every x with self.apply(x) == y; the map need not be one-to-one
+    fn preimage(&self, y: usize) -> Vec<usize> {
+        self.pieces
+            .iter()
+            .filter_map(|piece| {
+                let x = y as i128 - piece.offset as i128;
+                if piece.start as i128 <= x && x < piece.end as i128 {
+                    Some(x as usize)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    // the smallest value the range is mapped to
+    fn min_in(&self, range: &Range) -> Option<usize> {
+        self.pieces
+            .iter()
+            .filter(|piece| piece.start < range.1 && range.0 < piece.end)
+            .map(|piece| shifted(piece.start.max(range.0), piece.offset))
+            .min()
+    }
+
+    fn image_of(&self, ranges: &[Range]) -> Vec<Range> {
+        let mut image = vec![];
+        for range in ranges.iter() {
+            for piece in self.pieces.iter() {
+                let start = range.0.max(piece.start);
+                let end = range.1.min(piece.end);
+                if start < end {
+                    image.push(
+                        Piece {
+                            start,
+                            end,
+                            ..*piece
+                        }
+                        .image(),
+                    );
+                }
+            }
+        }
+        normalized(image)
+    }
+
+    fn preimage_of(&self, ranges: &[Range]) -> Vec<Range> {
+        let mut preimage = vec![];
+        for piece in self.pieces.iter() {
+            let image = piece.image();
+            for range in ranges.iter() {
+                let start = range.0.max(image.0);
+                let end = range.1.min(image.1);
+                if start < end {
+                    preimage.push(Range(
+                        shifted(start, -piece.offset),
+                        shifted(end, -piece.offset),
+                    ));
+                }
+            }
+        }
+        normalized(preimage)
+    }
+
+    // self first, then rhs
+    fn then(&self, rhs: &Self) -> Self {
+        let mut pieces: Vec<Piece> = vec![];
+        for piece in self.pieces.iter() {
+            let image = piece.image();
+            for next in rhs.pieces.iter() {
+                let start = image.0.max(next.start);
+                let end = image.1.min(next.end);
+                if start >= end {
+                    continue;
+                }
+                let offset = piece.offset + next.offset;
+                let start = shifted(start, -piece.offset);
+                let end = shifted(end, -piece.offset);
+                match pieces.last_mut() {
+                    Some(last) if last.offset == offset && last.end == start => last.end = end,
+                    _ => pieces.push(Piece { start, end, offset }),
+                }
+            }
+        }
+        Self { pieces }
     }
 }
 
@@ -93,6 +385,8 @@ struct Mapping {
 }
 #[derive(Debug)]
 struct Almanac {
+    source: String,
+    destination: String,
     mappings: Vec<Mapping>,
 }
 
@@ -158,36 +452,65 @@ impl Almanac {
         }
         src
     }
-    fn from(lines: &mut std::str::Lines) -> Self {
-        // find first line
-        loop {
-            let first_line = lines.next();
-            match first_line {
-                None => unreachable!(),
-                Some(line) => {
-                    if line.ends_with(":") {
-                        break;
-                    }
-                }
+    fn to_piecewise(&self) -> PiecewiseMap {
+        let mut mappings: Vec<&Mapping> = self.mappings.iter().collect();
+        mappings.sort_by_key(|mapping| mapping.src);
+        let mut pieces = vec![];
+        let mut at = 0;
+        for mapping in mappings {
+            if at < mapping.src {
+                pieces.push(Piece {
+                    start: at,
+                    end: mapping.src,
+                    offset: 0,
+                });
+            }
+            // mappings do not overlap in the input; if they did, the smaller source wins
+            let start = at.max(mapping.src);
+            let end = mapping.src + mapping.rng;
+            if start < end {
+                pieces.push(Piece {
+                    start,
+                    end,
+                    offset: mapping.dst as i64 - mapping.src as i64,
+                });
+                at = end;
             }
         }
+        pieces.push(Piece {
+            start: at,
+            end: usize::MAX,
+            offset: 0,
+        });
+        PiecewiseMap { pieces }
+    }
+    fn from(header: &str, lines: &mut std::str::Lines) -> Result<Self, ChainError> {
+        let (source, destination) = parse_header(header)?;
         let mut mappings = vec![];
         loop {
             let line = lines.next();
 
-            match line {
+            match line.map(|line| line.trim()) {
                 None => break,
                 Some("") => break,
                 Some(line) => {
-                    let mut line = line.trim().split_whitespace();
-                    let dst = line.next().unwrap().trim().parse().unwrap();
-                    let src = line.next().unwrap().trim().parse().unwrap();
-                    let rng = line.next().unwrap().trim().parse().unwrap();
+                    let nums: Vec<usize> = line
+                        .split_whitespace()
+                        .map(|word| word.parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| ChainError::Mapping(line.to_string()))?;
+                    let [dst, src, rng] = nums[..] else {
+                        return Err(ChainError::Mapping(line.to_string()));
+                    };
                     mappings.push(Mapping { src, dst, rng })
                 }
             }
         }
-        Self { mappings }
+        Ok(Self {
+            source,
+            destination,
+            mappings,
+        })
     }
 }
 
@@ -198,6 +521,8 @@ mod day05_tests {
     #[test]
     fn test_apply_to() {
         let almanac = Almanac {
+            source: "seed".to_string(),
+            destination: "soil".to_string(),
             mappings: vec![
                 Mapping {
                     src: 0,
@@ -267,4 +592,120 @@ mod day05_tests {
         let ans = Day05::solve_part_2(input);
         assert_eq!(ans, "46");
     }
+
+    #[test]
+    fn test_compose_chain() {
+        let input = Day05::test_input();
+        let mut lines = input.lines();
+        lines.next();
+        let almanacs = parse_almanacs(&mut lines).unwrap();
+        assert_eq!(almanacs.len(), 7);
+        let map = compose_chain(&almanacs).unwrap();
+
+        let seeds = [79, 14, 55, 13];
+        let locations: Vec<usize> = seeds.iter().map(|seed| map.apply(*seed)).collect();
+        assert_eq!(locations, vec![82, 43, 86, 35]);
+        for (seed, location) in seeds.iter().zip(locations) {
+            assert!(map.preimage(location).contains(seed));
+        }
+
+        // the composed map agrees with applying every almanac in turn
+        for seed in 0..120 {
+            let step_by_step = almanacs.iter().fold(seed, |s, almanac| almanac.apply(s));
+            assert_eq!(map.apply(seed), step_by_step);
+        }
+        assert!(map.pieces.windows(2).all(|w| w[0].end == w[1].start));
+
+        // past the last mapping everything falls into the identity piece
+        for x in [100, 500, usize::MAX - 1] {
+            assert_eq!(map.apply(x), x);
+            assert!(map.preimage(x).contains(&x));
+        }
+        let tail = Range(500, 600);
+        assert_eq!(map.preimage_of(&[tail]), vec![tail]);
+    }
+
+    #[test]
+    fn test_chain_errors() {
+        let input = "seed-to-soil map:
+50 98 2
+
+fertilizer-to-water map:
+49 53 8";
+        let almanacs = parse_almanacs(&mut input.lines()).unwrap();
+        assert_eq!(
+            compose_chain(&almanacs),
+            Err(ChainError::Broken {
+                expected: "soil".to_string(),
+                found: "fertilizer".to_string()
+            })
+        );
+        assert_eq!(
+            compose_chain(&almanacs[..1]),
+            Err(ChainError::Incomplete {
+                last: "soil".to_string()
+            })
+        );
+        assert_eq!(
+            parse_almanacs(&mut "seed to soil:\n1 2 3".lines()).unwrap_err(),
+            ChainError::Header("seed to soil:".to_string())
+        );
+        assert_eq!(
+            parse_almanacs(&mut "seed-to-soil map:\n1 2".lines()).unwrap_err(),
+            ChainError::Mapping("1 2".to_string())
+        );
+    }
+
+    #[test]
+    fn test_categories() {
+        let input = Day05::test_input();
+        let mut lines = input.lines();
+        lines.next();
+        let categories = Categories::from(parse_almanacs(&mut lines).unwrap());
+
+        // seed 79, soil 81, fertilizer 81, water 81, light 74, temperature 78, humidity 78
+        assert_eq!(
+            categories.convert_value(79, "seed", "location"),
+            Ok(vec![82])
+        );
+        assert_eq!(
+            categories.convert_value(81, "soil", "humidity"),
+            Ok(vec![78])
+        );
+        assert_eq!(
+            categories.convert_value(78, "humidity", "soil"),
+            Ok(vec![81])
+        );
+        assert!(categories
+            .convert_value(82, "location", "seed")
+            .unwrap()
+            .contains(&79));
+        assert_eq!(categories.convert_value(5, "soil", "soil"), Ok(vec![5]));
+
+        let locations = categories
+            .convert(&[Range(79, 93), Range(55, 68)], "seed", "location")
+            .unwrap();
+        assert_eq!(locations[0].0, 46);
+    }
+
+    #[test]
+    fn test_category_errors() {
+        let input = "seed-to-soil map:
+50 98 2
+
+water-to-light map:
+88 18 7";
+        let categories = Categories::from(parse_almanacs(&mut input.lines()).unwrap());
+        assert_eq!(
+            categories.convert_value(1, "seed", "light"),
+            Err(ChainError::Disconnected {
+                from: "seed".to_string(),
+                to: "light".to_string()
+            })
+        );
+        assert_eq!(
+            categories.convert_value(1, "seed", "humidity"),
+            Err(ChainError::Unknown("humidity".to_string()))
+        );
+    }
 }
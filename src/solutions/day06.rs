@@ -1,3 +1,5 @@
+use std::ops::{Add, Div, Mul, Sub};
+
 use super::Solution;
 
 pub struct Day06;
@@ -34,7 +36,7 @@ Distance:  9  40  200",
             .map(|(i, t)| {
                 let dist = distances[i];
 
-                valid_range(*t, dist)
+                valid_range(t, &dist)
             })
             .fold(1, |i, j| i * j)
             .to_string()
@@ -42,7 +44,7 @@ Distance:  9  40  200",
 
     fn solve_part_2(input: String) -> String {
         let mut lines = input.lines();
-        let time: usize = lines
+        let time: u128 = lines
             .next()
             .unwrap()
             .trim()
@@ -52,7 +54,7 @@ Distance:  9  40  200",
             .fold(String::new(), |a, b| a + &b)
             .parse()
             .unwrap();
-        let distance: usize = lines
+        let distance: u128 = lines
             .next()
             .unwrap()
             .trim()
@@ -62,32 +64,107 @@ Distance:  9  40  200",
             .fold(String::new(), |a, b| a + &b)
             .parse()
             .unwrap();
-        valid_range(time, distance).to_string()
+        valid_range(&time, &distance).to_string()
     }
 }
 
-fn valid_range(t: usize, dist: usize) -> usize {
-    // x * (t-x) > dist
-    // - x * x + tx - dist > 0
+// any integer wide enough for t * t: u64, u128, or BigUint past that; the
+// arithmetic goes through references so the big integer needn't be Copy
+trait RaceInt: Clone + Ord + From<u8> {
+    fn plus(&self, rhs: &Self) -> Self;
+    fn minus(&self, rhs: &Self) -> Self;
+    fn times(&self, rhs: &Self) -> Self;
+    fn over(&self, rhs: &Self) -> Self;
+}
+
+impl<T> RaceInt for T
+where
+    T: Clone + Ord + From<u8>,
+    for<'a> &'a T: Add<&'a T, Output = T>
+        + Sub<&'a T, Output = T>
+        + Mul<&'a T, Output = T>
+        + Div<&'a T, Output = T>,
+{
+    fn plus(&self, rhs: &Self) -> Self {
+        self + rhs
+    }
+
+    fn minus(&self, rhs: &Self) -> Self {
+        self - rhs
+    }
+
+    fn times(&self, rhs: &Self) -> Self {
+        self * rhs
+    }
+
+    fn over(&self, rhs: &Self) -> Self {
+        self / rhs
+    }
+}
+
+fn isqrt<T: RaceInt>(n: &T) -> T {
+    let one = T::from(1);
+    let two = T::from(2);
+    if *n < two {
+        return n.clone();
+    }
+    // newton's method, starting above the root
+    let mut x = n.over(&two).plus(&one);
+    let mut y = x.plus(&n.over(&x)).over(&two);
+    while y < x {
+        x = y;
+        y = x.plus(&n.over(&x)).over(&two);
+    }
+    x
+}
+
+// the hold times x with x * (t - x) > dist, as an inclusive range
+fn winning_holds<T: RaceInt>(t: &T, dist: &T) -> Option<(T, T)> {
+    let zero = T::from(0);
+    let one = T::from(1);
+    let two = T::from(2);
+    let four = T::from(4);
+    let wins = |x: &T| x.times(&t.minus(x)) > *dist;
+
+    // the best hold time is t / 2, so there is nothing to do if that does not win
+    if !wins(&t.over(&two)) {
+        return None;
+    }
+
+    // x * (t - x) > dist
     // x*x - tx + dist < 0
-    // (x - t/2)^2 + dist - t ^ 2 /4 < 0
-    // (x -  t/2) ^ 2 - (t^2/4 - dist) < 09
-    // (x - t / 2 + sqrt(t^2/4 - dist) (x - t/2 - sqrt(t^2/4 - dist)) < 0
-    // t/2 - sqrt(t^2/4 - dist) < x < t/2 + sqrt(t^2/4 - dist)
-    let t = t as f64;
-    let dist = dist as f64;
-    let min_x = t / 2.0 - f64::sqrt(t * t / 4.0 - dist);
-    let max_x = t / 2.0 + f64::sqrt(t * t / 4.0 - dist);
-
-    let min_x = min_x.floor() as usize;
-    let max_x = max_x.ceil() as usize;
-
-    max_x - min_x - 1
+    // (t - sqrt(t^2 - 4 dist)) / 2 < x < (t + sqrt(t^2 - 4 dist)) / 2
+    // t^2 > 4 dist, as t / 2 wins
+    let root = isqrt(&t.times(t).minus(&four.times(dist)));
+    let mut min_x = if root < *t {
+        t.minus(&root).over(&two)
+    } else {
+        zero.clone()
+    };
+    // the integer square root is off by less than one, so only a step or two is needed
+    while !wins(&min_x) {
+        min_x = min_x.plus(&one);
+    }
+    while min_x > zero && wins(&min_x.minus(&one)) {
+        min_x = min_x.minus(&one);
+    }
+
+    // symmetric around t / 2
+    let max_x = t.minus(&min_x);
+    Some((min_x, max_x))
+}
+
+fn valid_range<T: RaceInt>(t: &T, dist: &T) -> T {
+    match winning_holds(t, dist) {
+        Some((min_x, max_x)) => max_x.minus(&min_x).plus(&T::from(1)),
+        None => T::from(0),
+    }
 }
 
 #[cfg(test)]
 mod day06_tests {
     use super::*;
+    use crate::solutions::bignum::BigUint;
 
     #[test]
     fn test_part_1() {
@@ -102,4 +179,59 @@ mod day06_tests {
         let ans = Day06::solve_part_2(input);
         assert_eq!(ans, "71503");
     }
+
+    #[test]
+    fn test_winning_holds() {
+        assert_eq!(winning_holds(&7u64, &9), Some((2, 5)));
+        assert_eq!(winning_holds(&15u64, &40), Some((4, 11)));
+        // the record is tied at exactly 10 and 20
+        assert_eq!(winning_holds(&30u64, &200), Some((11, 19)));
+        // the best possible hold only ties the record
+        assert_eq!(winning_holds(&4u64, &4), None);
+        assert_eq!(winning_holds(&0u64, &0), None);
+        assert_eq!(valid_range(&4u64, &3), 1);
+    }
+
+    #[test]
+    fn test_large_race() {
+        let t: u128 = 7_153_071_530_715_307_153;
+        let dist: u128 = 940200_940200_940200_940200_940200;
+        let (min_x, max_x) = winning_holds(&t, &dist).unwrap();
+        let wins = |x: u128| x * (t - x) > dist;
+        assert!(wins(min_x) && !wins(min_x - 1));
+        assert!(wins(max_x) && !wins(max_x + 1));
+
+        // a tie at both ends: 10^18 * (3 * 10^18)
+        let (a, b): (u128, u128) = (1_000_000_000_000_000_000, 3_000_000_000_000_000_000);
+        assert_eq!(winning_holds(&(a + b), &(a * b)), Some((a + 1, b - 1)));
+    }
+
+    #[test]
+    fn test_big_race() {
+        // agrees with u128 where both fit
+        let t: u128 = 7_153_071_530_715_307_153;
+        let dist: u128 = 940200_940200_940200_940200_940200;
+        let (min_x, max_x) = winning_holds(&t, &dist).unwrap();
+        assert_eq!(
+            winning_holds(&BigUint::from(t), &BigUint::from(dist)),
+            Some((BigUint::from(min_x), BigUint::from(max_x)))
+        );
+
+        // t * t is past u128::MAX
+        let t = BigUint::from(u128::MAX);
+        let dist = &(&t * &t) / &BigUint::from(5u8);
+        let (min_x, max_x) = winning_holds(&t, &dist).unwrap();
+        let one = BigUint::from(1u8);
+        let wins = |x: &BigUint| x.times(&t.minus(x)) > dist;
+        assert!(wins(&min_x) && !wins(&min_x.minus(&one)));
+        assert!(wins(&max_x) && !wins(&max_x.plus(&one)));
+
+        // a tie at both ends: 2^100 * (3 * 2^100)
+        let a = BigUint::from(1u128 << 100);
+        let b = &a * &BigUint::from(3u8);
+        assert_eq!(
+            winning_holds(&(&a + &b), &(&a * &b)),
+            Some((a.plus(&one), b.minus(&one)))
+        );
+    }
 }
@@ -16,200 +16,294 @@ QQQJA 483",
     }
 
     fn solve_part_1(input: String) -> String {
-        let mut game: Vec<(CamelHand, usize)> = input
-            .lines()
-            .map(|l| {
-                let mut words = l.trim().split_whitespace();
-                let hand = words.next().unwrap().parse().unwrap();
-                let bid = words.next().unwrap().parse().unwrap();
-                (hand, bid)
-            })
-            .collect();
-        game.sort_by(|s, o| s.0.cmp(&o.0));
-        game.iter()
-            .enumerate()
-            .map(|(ind, (_, bid))| bid * (ind + 1))
-            .sum::<usize>()
-            .to_string()
+        winnings(&input, &Ruleset::standard()).unwrap().to_string()
     }
 
     fn solve_part_2(input: String) -> String {
-        let mut game: Vec<(CamelHand, usize)> = input
-            .lines()
-            .map(|l| {
-                let mut words = l.trim().split_whitespace();
-                let hand = words.next().unwrap().replace("J", "1").parse().unwrap();
-                let bid = words.next().unwrap().parse().unwrap();
-                (hand, bid)
-            })
-            .collect();
-        game.sort_by(|s, o| s.0.cmp(&o.0));
-
-        game.iter()
-            .enumerate()
-            .map(|(ind, (_, bid))| bid * (ind + 1))
-            .sum::<usize>()
-            .to_string()
+        winnings(&input, &Ruleset::jokers()).unwrap().to_string()
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Hash, Copy, Clone)]
-struct CamelCard(char);
-
-impl PartialOrd for CamelCard {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        let order = HashMap::from([
-            ('A', 14),
-            ('K', 13),
-            ('Q', 12),
-            ('J', 11),
-            ('T', 10),
-            ('9', 9),
-            ('8', 8),
-            ('7', 7),
-            ('6', 6),
-            ('5', 5),
-            ('4', 4),
-            ('3', 3),
-            ('2', 2),
-            ('1', 1), // to handle joker with convenience
-        ]);
-        let s = order.get(&self.0);
-        let o = order.get(&other.0);
-        if s.is_none() || o.is_none() {
-            panic!("sth wrong");
-        }
-        s.partial_cmp(&o)
-    }
+fn winnings(input: &str, rules: &Ruleset) -> Result<usize, RulesetError> {
+    rules.validate()?;
+    let mut game: Vec<(CamelHand, usize)> = input
+        .lines()
+        .map(|l| {
+            let mut words = l.split_whitespace();
+            let hand: CamelHand = words.next().unwrap().parse().unwrap();
+            if hand.cards.len() != rules.hand_size {
+                return Err(RulesetError::HandSize {
+                    expected: rules.hand_size,
+                    found: hand.cards.len(),
+                });
+            }
+            let bid = words.next().unwrap().parse().unwrap();
+            Ok((hand, bid))
+        })
+        .collect::<Result<_, _>>()?;
+    game.sort_by(|s, o| rules.compare(&s.0, &o.0));
+    Ok(game
+        .iter()
+        .enumerate()
+        .map(|(ind, (_, bid))| bid * (ind + 1))
+        .sum::<usize>())
 }
 
-impl Ord for CamelCard {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
-    }
+#[derive(Debug, PartialEq)]
+enum RulesetError {
+    // tie_break names a position past the end of a hand
+    TieBreakOutsideHand { position: usize, hand_size: usize },
+    // a hand with the wrong number of cards
+    HandSize { expected: usize, found: usize },
 }
 
-#[derive(PartialEq, Eq, Debug)]
-struct CamelHand {
-    cards: [CamelCard; 5],
+#[derive(Clone, Debug)]
+struct Ruleset {
+    // weakest to strongest
+    card_order: Vec<char>,
+    // cards that count as whatever makes the hand strongest
+    wild: Vec<char>,
+    // weakest to strongest
+    hand_types: Vec<CamelHandType>,
+    hand_size: usize,
+    // positions to compare, in order, when the hand types are the same;
+    // None compares every position from the left
+    tie_break: Option<Vec<usize>>,
 }
 
-impl PartialOrd for CamelHand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        let s = self.get_hand_type();
-        let o = other.get_hand_type();
-        match s.cmp(&o) {
-            Ordering::Equal => {
-                for i in 0..5 {
-                    let s = self.cards[i];
-                    let o = other.cards[i];
-                    if s != o {
-                        return s.partial_cmp(&o);
-                    }
-                }
-                Some(Ordering::Equal)
-            }
-            some => return Some(some),
+impl Ruleset {
+    fn standard() -> Self {
+        Self {
+            card_order: STANDARD_ORDER.chars().collect(),
+            wild: vec![],
+            hand_types: vec![
+                CamelHandType::HighCard,
+                CamelHandType::OnePair,
+                CamelHandType::TwoPairs,
+                CamelHandType::ThreeOfAKind,
+                CamelHandType::FullHouse,
+                CamelHandType::FourOfAKind,
+                CamelHandType::FiveOfAKind,
+            ],
+            hand_size: 5,
+            tie_break: None,
         }
     }
-}
 
-impl Ord for CamelHand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.partial_cmp(other).unwrap()
+    fn jokers() -> Self {
+        Self {
+            card_order: "J23456789TQKA".chars().collect(),
+            wild: vec!['J'],
+            ..Self::standard()
+        }
     }
-}
 
-impl CamelHand {
-    fn get_hand_type(&self) -> CamelHandType {
-        let mut counts: HashMap<CamelCard, usize> = HashMap::new();
-        for card in self.cards.iter() {
-            if let Some(num) = counts.get_mut(&card) {
-                *num += 1;
-            } else {
-                counts.insert(*card, 1);
-            }
+    // checked once up front, so compare can index hands by tie_break freely
+    fn validate(&self) -> Result<(), RulesetError> {
+        match self.tie_break().find(|&i| i >= self.hand_size) {
+            Some(position) => Err(RulesetError::TieBreakOutsideHand {
+                position,
+                hand_size: self.hand_size,
+            }),
+            None => Ok(()),
         }
+    }
+
+    fn tie_break(&self) -> impl Iterator<Item = usize> + '_ {
+        let default = if self.tie_break.is_none() {
+            0..self.hand_size
+        } else {
+            0..0
+        };
+        self.tie_break.iter().flatten().copied().chain(default)
+    }
 
-        let num_joker = counts.remove(&CamelCard('1')).unwrap_or(0);
+    fn card_rank(&self, card: CamelCard) -> usize {
+        match self.card_order.iter().position(|c| *c == card.0) {
+            Some(rank) => rank,
+            None => panic!("unknown card: {}", card.0),
+        }
+    }
 
-        match num_joker {
-            0 => self.get_hand_type_without_joker(),
-            4 | 5 => CamelHandType::FiveOfAKind,
-            3 => {
-                if counts.len() == 2 {
-                    CamelHandType::FourOfAKind
-                } else {
-                    CamelHandType::FiveOfAKind
-                }
-            }
-            2 => match counts.len() {
-                3 => CamelHandType::ThreeOfAKind,
-                2 => CamelHandType::FourOfAKind,
-                _ => CamelHandType::FiveOfAKind,
-            },
-            1 => match counts.len() {
-                4 => CamelHandType::OnePair,
-                3 => CamelHandType::ThreeOfAKind,
-                2 => match counts.values().max().unwrap() {
-                    3 => CamelHandType::FourOfAKind,
-                    _ => CamelHandType::FullHouse,
-                },
-                _ => CamelHandType::FiveOfAKind,
-            },
-            _ => unreachable!(),
+    fn hand_rank(&self, hand_type: CamelHandType) -> usize {
+        match self.hand_types.iter().position(|t| *t == hand_type) {
+            Some(rank) => rank,
+            None => panic!("hand type is not ranked: {:?}", hand_type),
         }
     }
-    fn get_hand_type_without_joker(&self) -> CamelHandType {
+
+    fn hand_type(&self, hand: &CamelHand) -> CamelHandType {
+        self.classify(hand).hand_type
+    }
+
+    fn classify(&self, hand: &CamelHand) -> Classification {
         let mut counts: HashMap<CamelCard, usize> = HashMap::new();
-        for card in self.cards.iter() {
-            if let Some(num) = counts.get_mut(&card) {
-                *num += 1;
+        let mut num_wild = 0;
+        for card in hand.cards.iter() {
+            if self.wild.contains(&card.0) {
+                num_wild += 1;
             } else {
-                counts.insert(*card, 1);
+                *counts.entry(*card).or_insert(0) += 1;
             }
         }
-        let l = counts.len();
-        match l {
-            5 => CamelHandType::HighCard,
-            4 => CamelHandType::OnePair,
-            3 => {
-                if *counts.values().max().unwrap() == 3 {
-                    CamelHandType::ThreeOfAKind
-                } else {
-                    CamelHandType::TwoPairs
-                }
+        let groups: Vec<(CamelCard, usize)> = self.sorted(counts.into_iter().collect());
+
+        // a wild card joins one of the groups or starts a new one with an unused
+        // card, strongest first; with a reordered hand_types joining the largest
+        // group isn't always best, so every choice is ranked
+        let mut targets: Vec<CamelCard> = groups.iter().map(|(card, _)| *card).collect();
+        targets.extend(
+            self.card_order
+                .iter()
+                .rev()
+                .map(|c| CamelCard(*c))
+                .filter(|card| {
+                    !self.wild.contains(&card.0) && !groups.iter().any(|(c, _)| c == card)
+                })
+                .take(num_wild),
+        );
+        if targets.is_empty() {
+            num_wild = 0;
+        }
+
+        // wild cards are interchangeable, so only non-decreasing choices are
+        // tried; the first best one wins, which prefers the larger, stronger groups
+        let mut choice = vec![0; num_wild];
+        let mut best: Option<(usize, CamelHandType, Vec<usize>)> = None;
+        loop {
+            let mut extra = vec![0; targets.len()];
+            for &t in choice.iter() {
+                extra[t] += 1;
             }
-            2 => {
-                if *counts.values().max().unwrap() == 4 {
-                    CamelHandType::FourOfAKind
-                } else {
-                    CamelHandType::FullHouse
+            let counts: Vec<usize> = self
+                .with_extra(&groups, &targets, &extra)
+                .iter()
+                .map(|(_, count)| *count)
+                .collect();
+            let hand_type = CamelHandType::from_counts(&counts);
+            let rank = self.hand_rank(hand_type);
+            if best
+                .as_ref()
+                .is_none_or(|(best_rank, _, _)| rank > *best_rank)
+            {
+                best = Some((rank, hand_type, choice.clone()));
+            }
+            // next non-decreasing choice
+            match choice.iter().rposition(|&t| t + 1 < targets.len()) {
+                Some(i) => {
+                    let t = choice[i] + 1;
+                    choice[i..].iter_mut().for_each(|c| *c = t);
                 }
+                None => break,
             }
-            1 => CamelHandType::FiveOfAKind,
-            _ => unreachable!(),
         }
+
+        let (_, hand_type, choice) = best.unwrap();
+        let mut extra = vec![0; targets.len()];
+        for &t in choice.iter() {
+            extra[t] += 1;
+        }
+        Classification {
+            hand_type,
+            substitutes: choice.iter().map(|&t| targets[t]).collect(),
+            multiset: self.with_extra(&groups, &targets, &extra),
+        }
+    }
+
+    // groups plus extra[i] more of targets[i], largest group first
+    fn with_extra(
+        &self,
+        groups: &[(CamelCard, usize)],
+        targets: &[CamelCard],
+        extra: &[usize],
+    ) -> Vec<(CamelCard, usize)> {
+        let multiset = targets
+            .iter()
+            .zip(extra)
+            .map(|(card, n)| {
+                let base = groups
+                    .iter()
+                    .find(|(c, _)| c == card)
+                    .map_or(0, |(_, count)| *count);
+                (*card, base + n)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        self.sorted(multiset)
+    }
+
+    // largest group first, stronger card first among groups of the same size
+    fn sorted(&self, mut multiset: Vec<(CamelCard, usize)>) -> Vec<(CamelCard, usize)> {
+        multiset
+            .sort_by(|(a, n), (b, m)| m.cmp(n).then(self.card_rank(*b).cmp(&self.card_rank(*a))));
+        multiset
+    }
+
+    // expects a validated ruleset and hands of hand_size cards
+    fn compare(&self, lhs: &CamelHand, rhs: &CamelHand) -> Ordering {
+        let s = self.hand_rank(self.hand_type(lhs));
+        let o = self.hand_rank(self.hand_type(rhs));
+        s.cmp(&o).then_with(|| {
+            self.tie_break()
+                .map(|i| {
+                    self.card_rank(lhs.cards[i])
+                        .cmp(&self.card_rank(rhs.cards[i]))
+                })
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+}
+
+// why a hand got its type
+#[derive(PartialEq, Eq, Debug)]
+struct Classification {
+    hand_type: CamelHandType,
+    // what each wild card stands for, in the order they appear
+    substitutes: Vec<CamelCard>,
+    // the cards after substitution, largest group first
+    multiset: Vec<(CamelCard, usize)>,
+}
+
+#[derive(PartialEq, Eq, Debug, Hash, Copy, Clone)]
+struct CamelCard(char);
+
+// weakest to strongest, part 1 rules
+const STANDARD_ORDER: &str = "23456789TJQKA";
+
+// without a ruleset, cards are ranked as in part 1
+impl PartialOrd for CamelCard {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
     }
 }
 
+impl Ord for CamelCard {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let rank = |card: &CamelCard| match STANDARD_ORDER.find(card.0) {
+            Some(rank) => rank,
+            None => panic!("unknown card: {}", card.0),
+        };
+        rank(self).cmp(&rank(other))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct CamelHand {
+    cards: Vec<CamelCard>,
+}
+
 impl FromStr for CamelHand {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let chars: Vec<char> = s.chars().collect();
-        let cards: [CamelCard; 5] = [
-            CamelCard(chars[0]),
-            CamelCard(chars[1]),
-            CamelCard(chars[2]),
-            CamelCard(chars[3]),
-            CamelCard(chars[4]),
-        ];
-        Ok(Self { cards })
+        Ok(Self {
+            cards: s.trim().chars().map(CamelCard).collect(),
+        })
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
 enum CamelHandType {
     HighCard,
     OnePair,
@@ -220,6 +314,23 @@ enum CamelHandType {
     FiveOfAKind,
 }
 
+impl CamelHandType {
+    // group sizes, largest first
+    fn from_counts(counts: &[usize]) -> Self {
+        let first = counts.first().copied().unwrap_or(0);
+        let second = counts.get(1).copied().unwrap_or(0);
+        match (first, second) {
+            (5.., _) => CamelHandType::FiveOfAKind,
+            (4, _) => CamelHandType::FourOfAKind,
+            (3, 2..) => CamelHandType::FullHouse,
+            (3, _) => CamelHandType::ThreeOfAKind,
+            (2, 2..) => CamelHandType::TwoPairs,
+            (2, _) => CamelHandType::OnePair,
+            _ => CamelHandType::HighCard,
+        }
+    }
+}
+
 #[cfg(test)]
 mod day07_tests {
     use super::*;
@@ -237,7 +348,7 @@ mod day07_tests {
         assert_eq!(
             hand,
             Ok(CamelHand {
-                cards: [
+                cards: vec![
                     CamelCard('7'),
                     CamelCard('T'),
                     CamelCard('Q'),
@@ -250,12 +361,74 @@ mod day07_tests {
 
     #[test]
     fn test_hand_with_joker() {
-        let hand = "Q11Q2".parse::<CamelHand>().unwrap().get_hand_type();
+        let rules = Ruleset::jokers();
+        let hand = rules.hand_type(&"QJJQ2".parse::<CamelHand>().unwrap());
         assert_eq!(hand, CamelHandType::FourOfAKind);
-        let hand = "T5515".parse::<CamelHand>().unwrap().get_hand_type();
+        let hand = rules.hand_type(&"T55J5".parse::<CamelHand>().unwrap());
         assert_eq!(hand, CamelHandType::FourOfAKind);
     }
 
+    #[test]
+    fn test_ruleset_variants() {
+        // both jokers and stars are wild
+        let rules = Ruleset {
+            card_order: "*J23456789TQKA".chars().collect(),
+            wild: vec!['J', '*'],
+            ..Ruleset::jokers()
+        };
+        let hand: CamelHand = "*J2K2".parse().unwrap();
+        assert_eq!(rules.hand_type(&hand), CamelHandType::FourOfAKind);
+
+        // compare from the last card
+        let rules = Ruleset {
+            tie_break: Some((0..5).rev().collect()),
+            ..Ruleset::standard()
+        };
+        let lhs: CamelHand = "23456".parse().unwrap();
+        let rhs: CamelHand = "65432".parse().unwrap();
+        assert_eq!(rules.compare(&lhs, &rhs), Ordering::Greater);
+        assert_eq!(Ruleset::standard().compare(&lhs, &rhs), Ordering::Less);
+
+        // three card hands, compared left to right without spelling it out
+        let rules = Ruleset {
+            hand_size: 3,
+            ..Ruleset::standard()
+        };
+        assert_eq!(winnings("KK2 10\nAKQ 1", &rules), Ok(21));
+        let lhs: CamelHand = "AK2".parse().unwrap();
+        let rhs: CamelHand = "AQK".parse().unwrap();
+        assert_eq!(rules.compare(&lhs, &rhs), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_invalid_ruleset() {
+        let rules = Ruleset {
+            hand_size: 3,
+            tie_break: Some(vec![0, 4]),
+            ..Ruleset::standard()
+        };
+        let err = RulesetError::TieBreakOutsideHand {
+            position: 4,
+            hand_size: 3,
+        };
+        assert_eq!(rules.validate(), Err(err));
+        assert!(winnings("KK2 10\nAKQ 1", &rules).is_err());
+
+        // the default tie-break follows the hand size
+        let rules = Ruleset {
+            hand_size: 3,
+            ..Ruleset::standard()
+        };
+        assert_eq!(rules.validate(), Ok(()));
+        assert_eq!(
+            winnings("KK2 10\nAKQJ 1", &rules),
+            Err(RulesetError::HandSize {
+                expected: 3,
+                found: 4
+            })
+        );
+    }
+
     #[test]
     fn test_part_1() {
         let input = Day07::test_input();
@@ -269,4 +442,156 @@ mod day07_tests {
         let ans = Day07::solve_part_2(input);
         assert_eq!(ans, "5905");
     }
+
+    #[test]
+    fn test_classify() {
+        let rules = Ruleset::jokers();
+        let classification = rules.classify(&"KTJJT".parse().unwrap());
+        assert_eq!(
+            classification,
+            Classification {
+                hand_type: CamelHandType::FourOfAKind,
+                substitutes: vec![CamelCard('T'), CamelCard('T')],
+                multiset: vec![(CamelCard('T'), 4), (CamelCard('K'), 1)],
+            }
+        );
+
+        let classification = rules.classify(&"JJJJJ".parse().unwrap());
+        assert_eq!(classification.hand_type, CamelHandType::FiveOfAKind);
+        assert_eq!(classification.substitutes, vec![CamelCard('A'); 5]);
+
+        let classification = Ruleset::standard().classify(&"KTJJT".parse().unwrap());
+        assert_eq!(classification.hand_type, CamelHandType::TwoPairs);
+        assert_eq!(classification.substitutes, vec![]);
+    }
+
+    // two pairs beat three of a kind
+    fn two_pairs_first() -> Ruleset {
+        Ruleset {
+            hand_types: vec![
+                CamelHandType::HighCard,
+                CamelHandType::OnePair,
+                CamelHandType::ThreeOfAKind,
+                CamelHandType::TwoPairs,
+                CamelHandType::FullHouse,
+                CamelHandType::FourOfAKind,
+                CamelHandType::FiveOfAKind,
+            ],
+            ..Ruleset::jokers()
+        }
+    }
+
+    #[test]
+    fn test_classify_reordered_hand_types() {
+        let rules = two_pairs_first();
+        let classification = rules.classify(&"KKQJ2".parse().unwrap());
+        assert_eq!(classification.hand_type, CamelHandType::TwoPairs);
+        assert_eq!(classification.substitutes, vec![CamelCard('Q')]);
+
+        // two jokers split up to make two pairs rather than four of a kind
+        let rules = Ruleset {
+            hand_types: vec![
+                CamelHandType::HighCard,
+                CamelHandType::OnePair,
+                CamelHandType::ThreeOfAKind,
+                CamelHandType::FullHouse,
+                CamelHandType::FourOfAKind,
+                CamelHandType::FiveOfAKind,
+                CamelHandType::TwoPairs,
+            ],
+            ..Ruleset::jokers()
+        };
+        let classification = rules.classify(&"KQJJ2".parse().unwrap());
+        assert_eq!(classification.hand_type, CamelHandType::TwoPairs);
+        assert_eq!(
+            classification.substitutes,
+            vec![CamelCard('K'), CamelCard('Q')]
+        );
+    }
+
+    // every multiset of `size` cards out of `cards`
+    fn multisets(cards: &[char], size: usize) -> Vec<Vec<char>> {
+        if size == 0 {
+            return vec![vec![]];
+        }
+        let mut ans = vec![];
+        for (i, card) in cards.iter().enumerate() {
+            for mut rest in multisets(&cards[i..], size - 1) {
+                rest.push(*card);
+                ans.push(rest);
+            }
+        }
+        ans
+    }
+
+    // tries every value for every wild card. wild cards are interchangeable,
+    // so values are assigned in non-decreasing order, starting from `from`
+    fn brute_force(rules: &Ruleset, cards: &[char], from: usize) -> CamelHandType {
+        let no_wild = Ruleset {
+            wild: vec![],
+            ..rules.clone()
+        };
+        let values: Vec<char> = rules
+            .card_order
+            .iter()
+            .filter(|c| !rules.wild.contains(c))
+            .copied()
+            .collect();
+        match cards.iter().position(|c| rules.wild.contains(c)) {
+            None => no_wild.hand_type(&CamelHand {
+                cards: cards.iter().map(|c| CamelCard(*c)).collect(),
+            }),
+            Some(i) => (from..values.len())
+                .map(|v| {
+                    let mut cards = cards.to_vec();
+                    cards[i] = values[v];
+                    brute_force(rules, &cards, v)
+                })
+                .max_by_key(|hand_type| rules.hand_rank(*hand_type))
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_classify_against_brute_force() {
+        let mut reversed = Ruleset::jokers();
+        reversed.hand_types.reverse();
+        for rules in [Ruleset::jokers(), two_pairs_first(), reversed] {
+            let no_wild = Ruleset {
+                wild: vec![],
+                ..rules.clone()
+            };
+            let hands = multisets(&rules.card_order, 5);
+            assert_eq!(hands.len(), 6188);
+            for cards in hands {
+                let hand = CamelHand {
+                    cards: cards.iter().map(|c| CamelCard(*c)).collect(),
+                };
+                let classification = rules.classify(&hand);
+                assert_eq!(
+                    classification.hand_type,
+                    brute_force(&rules, &cards, 0),
+                    "{:?}",
+                    classification
+                );
+
+                // the reported substitutions give the reported type
+                let mut substitutes = classification.substitutes.iter();
+                let substituted = CamelHand {
+                    cards: hand
+                        .cards
+                        .iter()
+                        .map(|card| match rules.wild.contains(&card.0) {
+                            true => *substitutes.next().unwrap(),
+                            false => *card,
+                        })
+                        .collect(),
+                };
+                assert_eq!(substitutes.next(), None);
+                assert_eq!(no_wild.hand_type(&substituted), classification.hand_type);
+                let size: usize = classification.multiset.iter().map(|(_, n)| n).sum();
+                assert_eq!(size, 5);
+            }
+        }
+    }
 }
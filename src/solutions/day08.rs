@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-
 use super::Solution;
 
 pub struct Day08;
@@ -21,54 +19,111 @@ ZZZ = (ZZZ, ZZZ)",
 
     fn solve_part_1(input: String) -> String {
         let lines = &mut input.lines();
-        let instructions: Vec<char> = lines.next().unwrap().chars().collect();
+        let instructions = parse_instructions(lines.next().unwrap());
         lines.next();
-        let mut graph = Graph::from(lines);
+        let graph = Graph::from(lines);
         graph.find_zzz(&instructions).to_string()
     }
 
     fn solve_part_2(input: String) -> String {
         let lines = &mut input.lines();
-        let instructions: Vec<char> = lines.next().unwrap().chars().collect();
+        let instructions = parse_instructions(lines.next().unwrap());
         lines.next();
-        let mut graph = Graph::from(lines);
-        let nodes: Vec<String> = graph
-            .nodes
-            .keys()
-            .filter_map(|k| {
-                if k.ends_with("A") {
-                    Some(k.clone())
-                } else {
-                    None
-                }
-            })
-            .collect();
-        nodes
+        let graph = Graph::from(lines);
+        let walks: Vec<GhostWalk> = graph
+            .starts()
             .into_iter()
-            .map(|node| {
-                graph.head = node;
-                graph.find_z(&instructions)
-            })
-            .fold(1, |a, b| lcm(a, b))
+            .map(|start| graph.ghost_walk(start, &instructions))
+            .collect();
+        simultaneous_z(&walks)
+            .expect("the ghosts never stand on Z nodes at the same time")
             .to_string()
     }
 }
 
-fn gcd(a: usize, b: usize) -> usize {
-    if b < a {
-        gcd(b, a)
-    } else {
-        if b % a == 0 {
-            a
+// the walk of a single ghost, as a sequence of (node, instruction index) states.
+// the states repeat after `prefix + cycle` steps, going back to step `prefix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GhostWalk {
+    prefix: usize,
+    cycle: usize,
+    // steps before the cycle at which the ghost is on a Z node
+    prefix_hits: Vec<usize>,
+    // steps in prefix..prefix + cycle at which the ghost is on a Z node
+    cycle_hits: Vec<usize>,
+}
+
+impl GhostWalk {
+    fn is_hit(&self, step: usize) -> bool {
+        if step < self.prefix {
+            self.prefix_hits.contains(&step)
         } else {
-            gcd(b % a, a)
+            let step = self.prefix + (step - self.prefix) % self.cycle;
+            self.cycle_hits.contains(&step)
         }
     }
 }
 
-fn lcm(a: usize, b: usize) -> usize {
-    let d = gcd(a, b);
-    a * b / d
+// the first step (>= 1) at which every ghost is on a Z node
+fn simultaneous_z(walks: &[GhostWalk]) -> Option<usize> {
+    let longest = walks.iter().max_by_key(|walk| walk.prefix)?;
+
+    // before every ghost is inside its cycle, only the hits of the slowest one can work
+    let early = longest
+        .prefix_hits
+        .iter()
+        .filter(|&&step| step > 0)
+        .find(|&&step| walks.iter().all(|walk| walk.is_hit(step)));
+    if let Some(step) = early {
+        return Some(*step);
+    }
+
+    // after that, each ghost is on Z at one of its cycle hits modulo its cycle length
+    let mut candidates: Vec<(i128, i128)> = vec![(0, 1)];
+    for walk in walks.iter() {
+        let cycle = walk.cycle as i128;
+        candidates = candidates
+            .iter()
+            .flat_map(|&candidate| {
+                walk.cycle_hits
+                    .iter()
+                    .filter_map(move |&hit| crt(candidate, (hit as i128 % cycle, cycle)))
+            })
+            .collect();
+        candidates.sort();
+        candidates.dedup();
+    }
+
+    let from = longest.prefix.max(1) as i128;
+    candidates
+        .into_iter()
+        .map(|(residue, modulus)| {
+            // the smallest step >= from with step % modulus == residue
+            from + (residue - from).rem_euclid(modulus)
+        })
+        .min()
+        .map(|step| step as usize)
+}
+
+// (g, x, y) with a * x + b * y = g
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+// merges x = r1 (mod m1) and x = r2 (mod m2), moduli need not be coprime
+fn crt((r1, m1): (i128, i128), (r2, m2): (i128, i128)) -> Option<(i128, i128)> {
+    let (g, p, _) = extended_gcd(m1, m2);
+    if (r2 - r1) % g != 0 {
+        return None;
+    }
+    let modulus = m1 / g * m2;
+    let k = ((r2 - r1) / g * p).rem_euclid(m2 / g);
+    Some(((r1 + m1 * k).rem_euclid(modulus), modulus))
 }
 
 fn parse_node_line(line: &str) -> (String, String, String) {
@@ -91,68 +146,155 @@ fn parse_node_line(line: &str) -> (String, String, String) {
     (first, second, third)
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Turn {
+    Left,
+    Right,
+}
+
+fn parse_instructions(line: &str) -> Vec<Turn> {
+    line.trim()
+        .chars()
+        .map(|char| match char {
+            'L' => Turn::Left,
+            'R' => Turn::Right,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+// nodes are interned to ids (sorted by name), so a step is a single array lookup
 struct Graph {
-    head: String,
-    nodes: HashMap<String, Node>,
+    names: Vec<String>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    is_z: Vec<bool>,
 }
 
 impl Graph {
-    fn find_z(&mut self, instructions: &[char]) -> usize {
-        let l = instructions.len();
-        let mut i = 0;
-        loop {
-            let j = i % l;
-            let instruction = instructions[j];
-            self.handle_instruction(instruction);
-            if self.head.ends_with("Z") {
-                break i + 1;
-            }
-            i += 1;
+    fn id(&self, name: &str) -> Option<usize> {
+        self.names.binary_search_by(|n| n.as_str().cmp(name)).ok()
+    }
+
+    fn next(&self, node: usize, turn: Turn) -> usize {
+        match turn {
+            Turn::Left => self.left[node],
+            Turn::Right => self.right[node],
+        }
+    }
+
+    // a state is (node, index of the next instruction)
+    fn step(&self, (node, i): (usize, usize), instructions: &[Turn]) -> (usize, usize) {
+        let next = self.next(node, instructions[i]);
+        if i + 1 == instructions.len() {
+            (next, 0)
+        } else {
+            (next, i + 1)
+        }
+    }
+
+    fn starts(&self) -> Vec<usize> {
+        (0..self.names.len())
+            .filter(|&id| self.names[id].ends_with('A'))
+            .collect()
+    }
+
+    fn find_zzz(&self, instructions: &[Turn]) -> usize {
+        let end = self.id("ZZZ").unwrap();
+        let mut state = (self.id("AAA").unwrap(), 0);
+        let mut steps = 0;
+        while state.0 != end || steps == 0 {
+            state = self.step(state, instructions);
+            steps += 1;
         }
+        steps
     }
-    fn find_zzz(&mut self, instructions: &[char]) -> usize {
-        let l = instructions.len();
-        let mut i = 0;
-        loop {
-            let j = i % l;
-            let instruction = instructions[j];
-            self.handle_instruction(instruction);
-            if self.head == "ZZZ" {
-                break i + 1;
+
+    fn ghost_walk(&self, start: usize, instructions: &[Turn]) -> GhostWalk {
+        // brent's cycle detection, so no table of seen states is needed
+        let start = (start, 0);
+        let mut power = 1;
+        let mut cycle = 1;
+        let mut tortoise = start;
+        let mut hare = self.step(start, instructions);
+        while tortoise != hare {
+            if power == cycle {
+                tortoise = hare;
+                power *= 2;
+                cycle = 0;
+            }
+            hare = self.step(hare, instructions);
+            cycle += 1;
+        }
+
+        let mut tortoise = start;
+        let mut hare = start;
+        for _ in 0..cycle {
+            hare = self.step(hare, instructions);
+        }
+        let mut prefix = 0;
+        while tortoise != hare {
+            tortoise = self.step(tortoise, instructions);
+            hare = self.step(hare, instructions);
+            prefix += 1;
+        }
+
+        let mut prefix_hits = vec![];
+        let mut cycle_hits = vec![];
+        let mut state = start;
+        for step in 0..(prefix + cycle) {
+            if self.is_z[state.0] {
+                if step < prefix {
+                    prefix_hits.push(step);
+                } else {
+                    cycle_hits.push(step);
+                }
             }
-            i += 1;
+            state = self.step(state, instructions);
+        }
+        GhostWalk {
+            prefix,
+            cycle,
+            prefix_hits,
+            cycle_hits,
         }
     }
-    fn handle_instruction(&mut self, instruction: char) {
-        match instruction {
-            'L' => {
-                self.head = self.nodes.get(&self.head).unwrap().left.clone();
+
+    // moves every ghost one step at a time, until all of them are on Z at once
+    fn lockstep(
+        &self,
+        heads: &mut [usize],
+        instructions: &[Turn],
+        max_steps: usize,
+    ) -> Option<usize> {
+        for (step, &turn) in instructions.iter().cycle().take(max_steps).enumerate() {
+            for head in heads.iter_mut() {
+                *head = self.next(*head, turn);
             }
-            'R' => {
-                self.head = self.nodes.get(&self.head).unwrap().right.clone();
+            if heads.iter().all(|&head| self.is_z[head]) {
+                return Some(step + 1);
             }
-            _ => unreachable!(),
         }
+        None
     }
+
     fn from(lines: &mut std::str::Lines) -> Self {
-        let mut nodes = HashMap::new();
-        for line in lines {
-            let (head, left, right) = parse_node_line(line);
-            nodes.insert(head, Node { left, right });
-        }
+        let mut parsed: Vec<(String, String, String)> = lines.map(parse_node_line).collect();
+        parsed.sort();
+        let names: Vec<String> = parsed.iter().map(|(name, _, _)| name.clone()).collect();
+        let id = |name: &String| names.binary_search(name).unwrap();
+        let left = parsed.iter().map(|(_, left, _)| id(left)).collect();
+        let right = parsed.iter().map(|(_, _, right)| id(right)).collect();
+        let is_z = names.iter().map(|name| name.ends_with('Z')).collect();
         Self {
-            head: "AAA".to_string(),
-            nodes,
+            names,
+            left,
+            right,
+            is_z,
         }
     }
 }
 
-#[derive(Clone)]
-struct Node {
-    left: String,
-    right: String,
-}
-
 #[cfg(test)]
 mod day08_tests {
     use super::*;
@@ -181,4 +323,70 @@ XXX = (XXX, XXX)",
         let ans = Day08::solve_part_2(input);
         assert_eq!(ans, "6");
     }
+
+    #[test]
+    fn test_ghost_walk() {
+        let input = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+        let lines = &mut input.lines();
+        let instructions = parse_instructions(lines.next().unwrap());
+        lines.next();
+        let graph = Graph::from(lines);
+        assert_eq!(
+            graph.ghost_walk(graph.id("22A").unwrap(), &instructions),
+            GhostWalk {
+                prefix: 1,
+                cycle: 6,
+                prefix_hits: vec![],
+                cycle_hits: vec![3, 6],
+            }
+        );
+
+        let mut heads = graph.starts();
+        assert_eq!(graph.lockstep(&mut heads, &instructions, 100), Some(6));
+        let names: Vec<&str> = heads.iter().map(|&id| graph.names[id].as_str()).collect();
+        assert_eq!(names, vec!["11Z", "22Z"]);
+    }
+
+    #[test]
+    fn test_simultaneous_z() {
+        // on Z at step 2, then every 3 steps from step 4: 2, 4, 7, 10, ...
+        let lhs = GhostWalk {
+            prefix: 3,
+            cycle: 3,
+            prefix_hits: vec![2],
+            cycle_hits: vec![4],
+        };
+        // on Z at every even step: a plain lcm would say 6
+        let rhs = GhostWalk {
+            prefix: 0,
+            cycle: 2,
+            prefix_hits: vec![],
+            cycle_hits: vec![0],
+        };
+        assert_eq!(simultaneous_z(&[lhs.clone(), rhs.clone()]), Some(2));
+
+        let lhs = GhostWalk {
+            prefix_hits: vec![],
+            ..lhs
+        };
+        assert_eq!(simultaneous_z(&[lhs, rhs.clone()]), Some(4));
+
+        // odd steps only
+        let odd = GhostWalk {
+            prefix: 0,
+            cycle: 2,
+            prefix_hits: vec![],
+            cycle_hits: vec![1],
+        };
+        assert_eq!(simultaneous_z(&[odd, rhs]), None);
+    }
 }
@@ -1,5 +1,3 @@
-use std::{cell::RefCell, rc::Rc};
-
 use super::Solution;
 
 pub struct Day09;
@@ -16,16 +14,8 @@ impl Solution for Day09 {
     fn solve_part_1(input: String) -> String {
         input
             .lines()
-            .map(|line| {
-                let nums: Vec<i64> = line
-                    .trim()
-                    .split_whitespace()
-                    .map(|w| w.trim().parse().unwrap())
-                    .collect();
-                nums
-            })
-            .map(|nums| OasisHistory::from(&nums))
-            .map(|oasis| oasis.predict())
+            .map(|line| DifferenceTable::from(&parse_history(line)).unwrap())
+            .map(|table| table.forward(1))
             .sum::<i64>()
             .to_string()
     }
@@ -33,54 +23,104 @@ impl Solution for Day09 {
     fn solve_part_2(input: String) -> String {
         input
             .lines()
-            .map(|line| {
-                let nums: Vec<i64> = line
-                    .trim()
-                    .split_whitespace()
-                    .map(|w| w.trim().parse().unwrap())
-                    .rev()
-                    .collect();
-                nums
-            })
-            .map(|nums| OasisHistory::from(&nums))
-            .map(|oasis| oasis.predict())
+            .map(|line| DifferenceTable::from(&parse_history(line)).unwrap())
+            .map(|table| table.backward(1))
             .sum::<i64>()
             .to_string()
     }
 }
 
-#[derive(Debug)]
-enum OasisHistory {
-    Cons(i64, Rc<RefCell<OasisHistory>>),
-    Nil,
+fn parse_history(line: &str) -> Vec<i64> {
+    line.split_whitespace()
+        .map(|w| w.parse().unwrap())
+        .collect()
 }
 
-impl OasisHistory {
-    fn from(nums: &[i64]) -> Self {
-        let diffs: Vec<i64> = nums
-            .iter()
-            .zip(nums.iter().skip(1))
-            .map(|(prev, next)| next - prev)
-            .collect();
-        if nums
-            .iter()
-            .filter_map(|&n| if n == 0 { None } else { Some(()) })
-            .count()
-            == 0
-        {
-            Self::Nil
-        } else {
-            Self::Cons(
-                *nums.last().unwrap(),
-                Rc::new(RefCell::new(Self::from(&diffs))),
-            )
+#[derive(Debug, PartialEq, Eq)]
+enum OasisError {
+    Empty,
+    // the differences ran out before a row of zeros showed up
+    NeverZero { rows: usize },
+}
+
+// the history and its differences, down to the first row of zeros
+#[derive(Debug, PartialEq, Eq)]
+struct DifferenceTable {
+    rows: Vec<Vec<i64>>,
+}
+
+impl DifferenceTable {
+    fn from(nums: &[i64]) -> Result<Self, OasisError> {
+        if nums.is_empty() {
+            return Err(OasisError::Empty);
+        }
+        let mut rows = vec![nums.to_vec()];
+        loop {
+            let last = rows.last().unwrap();
+            if last.iter().all(|n| *n == 0) {
+                break;
+            }
+            if last.len() == 1 {
+                return Err(OasisError::NeverZero { rows: rows.len() });
+            }
+            let diffs: Vec<i64> = last.windows(2).map(|w| w[1] - w[0]).collect();
+            rows.push(diffs);
         }
+        Ok(Self { rows })
+    }
+
+    // the history is a polynomial of this degree; the last row is all zeros
+    fn degree(&self) -> usize {
+        self.rows.len().saturating_sub(2)
     }
-    fn predict(&self) -> i64 {
-        match self {
-            Self::Nil => 0,
-            Self::Cons(last_value, child) => last_value + child.borrow().predict(),
+
+    // the value at index x of the history, which may be outside of it.
+    // newton's forward differences: f(x) = sum of C(x, j) * (first of row j)
+    fn value_at(&self, x: i64) -> i64 {
+        let x = x as i128;
+        let mut binomial: i128 = 1;
+        let mut ans: i128 = 0;
+        for (j, row) in self.rows.iter().enumerate() {
+            ans += binomial * row[0] as i128;
+            // C(x, j + 1) = C(x, j) * (x - j) / (j + 1), always exact
+            binomial = binomial * (x - j as i128) / (j as i128 + 1);
         }
+        ans as i64
+    }
+
+    // k steps after the last value
+    fn forward(&self, k: i64) -> i64 {
+        self.value_at(self.rows[0].len() as i64 - 1 + k)
+    }
+
+    // k steps before the first value
+    fn backward(&self, k: i64) -> i64 {
+        self.value_at(-k)
+    }
+}
+
+impl std::fmt::Display for DifferenceTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .rows
+            .iter()
+            .flatten()
+            .map(|n| n.to_string().len())
+            .max()
+            .unwrap_or(0)
+            + 2;
+        let width = width + width % 2;
+        let lines: Vec<String> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let indent = " ".repeat(i * width / 2);
+                let nums: String = row.iter().map(|n| format!("{:>width$}", n)).collect();
+                format!("{}{}", indent, nums)
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))
     }
 }
 
@@ -101,4 +141,41 @@ mod day09_tests {
         let ans = Day09::solve_part_2(input);
         assert_eq!(ans, "2");
     }
+
+    #[test]
+    fn test_difference_table() {
+        let table = DifferenceTable::from(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(table.degree(), 3);
+        assert_eq!(table.forward(1), 68);
+        assert_eq!(table.backward(1), 5);
+        // the history itself
+        for (i, n) in [10, 13, 16, 21, 30, 45].iter().enumerate() {
+            assert_eq!(table.value_at(i as i64), *n);
+        }
+        // 45 + 23 = 68, then 68 + 33 = 101
+        assert_eq!(table.forward(2), 101);
+        assert_eq!(table.backward(2), -4);
+        assert_eq!(table.backward(3), -19);
+
+        assert_eq!(
+            table.to_string(),
+            "  10  13  16  21  30  45
+     3   3   5   9  15
+       0   2   4   6
+         2   2   2
+           0   0"
+        );
+    }
+
+    #[test]
+    fn test_never_zero() {
+        assert_eq!(
+            DifferenceTable::from(&[1, 2, 4, 8]),
+            Err(OasisError::NeverZero { rows: 4 })
+        );
+        assert_eq!(DifferenceTable::from(&[]), Err(OasisError::Empty));
+        let table = DifferenceTable::from(&[7, 7]).unwrap();
+        assert_eq!(table.forward(100), 7);
+        assert_eq!(DifferenceTable::from(&[0]).unwrap().forward(3), 0);
+    }
 }
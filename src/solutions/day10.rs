@@ -1,4 +1,4 @@
-use std::{collections::VecDeque, str::FromStr};
+use std::str::FromStr;
 
 use super::Solution;
 
@@ -43,172 +43,154 @@ struct Diagram {
     tiles: Vec<Vec<Tile>>,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 struct Pos(usize, usize);
 
 impl Diagram {
     fn get_size(&self) -> Pos {
         Pos(self.tiles.len(), self.tiles[0].len())
     }
-    fn get_animal(&self) -> Pos {
-        let Pos(max_r, max_c) = self.get_size();
-        for r in 0..max_r {
-            for c in 0..max_c {
-                match self.tiles[r][c] {
-                    Tile::Animal => return Pos(r, c),
-                    _ => {}
-                }
-            }
+    fn get_animal(&self) -> Result<Pos, StartError> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .find_map(|(r, row)| {
+                row.iter()
+                    .position(|tile| matches!(tile, Tile::Animal))
+                    .map(|c| Pos(r, c))
+            })
+            .ok_or(StartError::Missing)
+    }
+
+    fn get_animal_type(&self) -> Result<Pipe, StartError> {
+        self.infer_start().map(|(pipe, _)| pipe)
+    }
+
+    // try every shape on the animal's tile, keep the one whose walk closes a loop
+    fn infer_start(&self) -> Result<(Pipe, Vec<Pos>), StartError> {
+        let start = self.get_animal()?;
+        let mut candidates: Vec<(Pipe, Vec<Pos>)> = Pipe::ALL
+            .into_iter()
+            .filter_map(|pipe| self.walk(start, pipe).map(|path| (pipe, path)))
+            .collect();
+        match candidates.len() {
+            0 => Err(StartError::NoLoop),
+            1 => Ok(candidates.pop().unwrap()),
+            _ => Err(StartError::Ambiguous(
+                candidates.into_iter().map(|(pipe, _)| pipe).collect(),
+            )),
         }
-        Pos(0, 0)
-    }
-    fn get_animal_type(&self) -> Pipe {
-        let pos = self.get_animal();
-        let north = self.connected_to(pos, Dir::North).is_some();
-        let south = self.connected_to(pos, Dir::South).is_some();
-        let east = self.connected_to(pos, Dir::East).is_some();
-        let west = self.connected_to(pos, Dir::West).is_some();
-
-        if north && south {
-            Pipe::Vertical
-        } else if north && east {
-            Pipe::NorthEast
-        } else if north && west {
-            Pipe::NorthWest
-        } else if south && east {
-            Pipe::SouthEast
-        } else if south && west {
-            Pipe::SouthWest
-        } else if east && west {
-            Pipe::Horizontal
-        } else {
-            unreachable!()
+    }
+
+    // tiles from start out through pipe's first exit, if it comes back through the other
+    fn walk(&self, start: Pos, pipe: Pipe) -> Option<Vec<Pos>> {
+        let [first, last] = pipe.exits();
+        let mut path = vec![start];
+        let mut heading = first;
+        let mut pos = self.adj(start, heading)?;
+        while pos != start {
+            let Tile::Pipe(here) = self.tiles[pos.0][pos.1] else {
+                return None;
+            };
+            heading = match here.exits() {
+                [a, b] if a == heading.opposite() => b,
+                [a, b] if b == heading.opposite() => a,
+                _ => return None,
+            };
+            path.push(pos);
+            pos = self.adj(pos, heading)?;
         }
+        (heading.opposite() == last).then_some(path)
     }
 
     fn part_2(&self) -> usize {
-        // sketch:
-        let Pos(r, c) = self.get_size();
-        let max_r = 3 * r;
-        let max_c = 3 * c;
-        let mut tripled = vec![vec![false; max_c]; max_r];
-        for pos in self.get_loop() {
-            let tile = &self.tiles[pos.0][pos.1];
-            if let Tile::Pipe(pipe) = tile {
-                let (sr, sc) = (pos.0 * 3, pos.1 * 3);
-                let tr = pipe.tripled();
-                for r in 0..3 {
-                    for c in 0..3 {
-                        tripled[sr + r][sc + c] = tr[r][c];
-                    }
-                }
-            }
-        }
-        let pipe = self.get_animal_type();
-        let pos = self.get_animal();
-        let (sr, sc) = (pos.0 * 3, pos.1 * 3);
-        let tr = pipe.tripled();
-        for r in 0..3 {
-            for c in 0..3 {
-                tripled[sr + r][sc + c] = tr[r][c];
-            }
-        }
-        let mut stack = vec![Pos(0, 0)];
+        self.analyze().unwrap().enclosed
+    }
 
-        tripled[0][0] = true;
+    fn part_1(&self) -> usize {
+        self.analyze().unwrap().distance
+    }
 
-        while let Some(pos) = stack.pop() {
-            let mut next = vec![];
-            if pos.0 > 0 {
-                next.push(Pos(pos.0 - 1, pos.1));
-            }
-            if pos.1 > 0 {
-                next.push(Pos(pos.0, pos.1 - 1));
-            }
-            if pos.0 + 1 < max_r {
-                next.push(Pos(pos.0 + 1, pos.1));
-            }
-            if pos.1 + 1 < max_c {
-                next.push(Pos(pos.0, pos.1 + 1));
-            }
-            for n in next.into_iter() {
-                if !tripled[n.0][n.1] {
-                    tripled[n.0][n.1] = true;
+    // the main loop in walking order from the animal, i.e. its polygon
+    fn get_loop(&self) -> Result<Vec<Pos>, StartError> {
+        self.infer_start().map(|(_, path)| path)
+    }
 
-                    stack.push(n);
-                }
-            }
-        }
+    // shoelace for the area, then pick's theorem A = i + b/2 - 1 for the interior
+    fn analyze(&self) -> Result<LoopReport, StartError> {
+        let (start_pipe, path) = self.infer_start()?;
+        let length = path.len();
+        let twice_area: i64 = path
+            .iter()
+            .zip(path.iter().cycle().skip(1))
+            .map(|(a, b)| a.1 as i64 * b.0 as i64 - b.1 as i64 * a.0 as i64)
+            .sum();
+        // rows grow downwards, so a positive sum is clockwise on screen
+        let orientation = if twice_area >= 0 {
+            Orientation::Clockwise
+        } else {
+            Orientation::CounterClockwise
+        };
+        let enclosed = (twice_area.unsigned_abs() as usize + 2).saturating_sub(length) / 2;
 
         let Pos(max_r, max_c) = self.get_size();
-
-        let mut count = 0;
-
-        for r in 0..max_r {
-            for c in 0..max_c {
-                let sr = 3 * r;
-                let sc = 3 * c;
-                let mut local_count = 0;
-                for x in 0..3 {
-                    for y in 0..3 {
-                        if tripled[sr + x][sc + y] {
-                            local_count += 1;
-                        }
-                    }
-                }
-                if local_count == 0 {
-                    count += 1;
-                };
-            }
+        let mut pipes = vec![vec![None; max_c]; max_r];
+        for &pos in &path {
+            pipes[pos.0][pos.1] = Some(match self.tiles[pos.0][pos.1] {
+                Tile::Pipe(pipe) => pipe,
+                _ => start_pipe,
+            });
         }
 
-        count
+        Ok(LoopReport {
+            start: path[0],
+            length,
+            farthest: path[length / 2],
+            distance: length / 2,
+            orientation,
+            enclosed,
+            pipes,
+        })
     }
 
-    fn get_loop(&self) -> Vec<Pos> {
-        // DFS, unlike part 1
-        let mut ans = vec![];
-        let mut stack = vec![];
-        let start = self.get_animal();
-        stack.push(start);
-        let mut visited: Vec<Vec<bool>> = self
-            .tiles
-            .iter()
-            .map(|row| row.iter().map(|_| false).collect())
-            .collect();
-        visited[start.0][start.1] = true;
-        while let Some(now) = stack.pop() {
-            for next in self.connections(now) {
-                if !visited[next.0][next.1] {
-                    visited[next.0][next.1] = true;
-                    stack.push(next);
-                    ans.push(next);
-                }
-            }
+    // box-drawing map with loop, start and inside/outside told apart
+    fn render(&self, style: RenderStyle) -> Result<String, StartError> {
+        let report = self.analyze()?;
+        let start = report.start;
+        let Pos(max_r, max_c) = self.get_size();
+        let mut inside = vec![vec![false; max_c]; max_r];
+        for pos in report.enclosed_tiles() {
+            inside[pos.0][pos.1] = true;
         }
-        ans
-    }
 
-    fn part_1(&self) -> usize {
-        let now = self.get_animal();
-        let mut queue: VecDeque<(Pos, usize)> = VecDeque::from([(now, 0)]);
-        let mut visited: Vec<Vec<bool>> = self
-            .tiles
-            .iter()
-            .map(|row| row.iter().map(|_| false).collect())
-            .collect();
-        let mut ans = 0;
-        while let Some((pos, dist)) = queue.pop_front() {
-            ans = ans.max(dist);
-            for next_pos in self.connections(pos) {
-                if !visited[next_pos.0][next_pos.1] {
-                    visited[next_pos.0][next_pos.1] = true;
-                    queue.push_back((next_pos, dist + 1));
-                }
+        let mut out = String::new();
+        for (r, row) in self.tiles.iter().enumerate() {
+            for (c, tile) in row.iter().enumerate() {
+                let on_loop = report.pipes[r][c];
+                let cell = match style {
+                    RenderStyle::Glyphs => match on_loop {
+                        Some(pipe) if Pos(r, c) == start => pipe.heavy_glyph().to_string(),
+                        Some(pipe) => pipe.glyph().to_string(),
+                        None if inside[r][c] => String::from("●"),
+                        None => String::from("·"),
+                    },
+                    RenderStyle::Ansi => {
+                        let (colour, glyph) = match (on_loop, tile) {
+                            (Some(pipe), _) if Pos(r, c) == start => ("1;31", pipe.glyph()),
+                            (Some(pipe), _) => ("32", pipe.glyph()),
+                            (None, Tile::Pipe(pipe)) => {
+                                (if inside[r][c] { "30;43" } else { "2" }, pipe.glyph())
+                            }
+                            (None, _) => (if inside[r][c] { "30;43" } else { "2" }, '.'),
+                        };
+                        format!("\x1b[{colour}m{glyph}\x1b[0m")
+                    }
+                };
+                out.push_str(&cell);
             }
+            out.push('\n');
         }
-
-        ans
+        Ok(out)
     }
 
     fn adj(&self, pos: Pos, dir: Dir) -> Option<Pos> {
@@ -250,42 +232,43 @@ impl Diagram {
             None
         }
     }
+}
 
-    fn connected_to(&self, pos: Pos, dir: Dir) -> Option<Pos> {
-        let now = &self.tiles[pos.0][pos.1];
-        if let Some(next_pos) = self.adj(pos, dir) {
-            let next = &self.tiles[next_pos.0][next_pos.1];
-            match dir {
-                Dir::East => {
-                    if now.heads_east() && next.heads_west() {
-                        return Some(next_pos);
-                    }
-                }
-                Dir::West => {
-                    if now.heads_west() && next.heads_east() {
-                        return Some(next_pos);
-                    }
-                }
-                Dir::North => {
-                    if now.heads_north() && next.heads_south() {
-                        return Some(next_pos);
-                    }
-                }
-                Dir::South => {
-                    if now.heads_south() && next.heads_north() {
-                        return Some(next_pos);
-                    }
-                }
-            }
-        }
-        None
-    }
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Orientation {
+    Clockwise,
+    CounterClockwise,
+}
 
-    fn connections(&self, pos: Pos) -> Vec<Pos> {
-        [Dir::North, Dir::South, Dir::East, Dir::West]
-            .into_iter()
-            .filter_map(|dir| self.connected_to(pos, dir))
-            .collect()
+#[derive(Debug)]
+struct LoopReport {
+    start: Pos,
+    length: usize,
+    farthest: Pos,
+    distance: usize,
+    orientation: Orientation,
+    enclosed: usize,
+    // loop pipe on each tile, animal's pipe resolved
+    pipes: Vec<Vec<Option<Pipe>>>,
+}
+
+impl LoopReport {
+    // scanline parity: only north-heading pipes flip, so L--7 flips once, L--J never
+    fn enclosed_tiles(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.pipes.iter().enumerate().flat_map(|(r, row)| {
+            let mut inside = false;
+            row.iter()
+                .enumerate()
+                .filter_map(move |(c, pipe)| match pipe {
+                    Some(pipe) => {
+                        if pipe.exits().contains(&Dir::North) {
+                            inside = !inside;
+                        }
+                        None
+                    }
+                    None => inside.then_some(Pos(r, c)),
+                })
+        })
     }
 }
 
@@ -301,6 +284,14 @@ impl FromStr for Diagram {
 }
 
 #[derive(Debug, Copy, Clone)]
+enum RenderStyle {
+    // heavy pipe for the start, ● inside, · outside
+    Glyphs,
+    // green loop, red start, inside on yellow, outside dimmed; junk pipes kept
+    Ansi,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum Dir {
     East,
     West,
@@ -308,6 +299,27 @@ enum Dir {
     North,
 }
 
+impl Dir {
+    fn opposite(&self) -> Self {
+        match self {
+            Dir::East => Dir::West,
+            Dir::West => Dir::East,
+            Dir::South => Dir::North,
+            Dir::North => Dir::South,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum StartError {
+    // no S on the map
+    Missing,
+    // no shape on the start closes a loop
+    NoLoop,
+    // several shapes close a loop, each a different one
+    Ambiguous(Vec<Pipe>),
+}
+
 #[derive(Debug)]
 enum Tile {
     Pipe(Pipe),
@@ -316,46 +328,6 @@ enum Tile {
 }
 
 impl Tile {
-    fn heads_north(&self) -> bool {
-        match self {
-            Tile::Pipe(Pipe::NorthEast)
-            | Tile::Pipe(Pipe::NorthWest)
-            | Tile::Pipe(Pipe::Vertical)
-            | Tile::Animal => true,
-            _ => false,
-        }
-    }
-
-    fn heads_south(&self) -> bool {
-        match self {
-            Tile::Pipe(Pipe::SouthEast)
-            | Tile::Pipe(Pipe::SouthWest)
-            | Tile::Pipe(Pipe::Vertical)
-            | Tile::Animal => true,
-            _ => false,
-        }
-    }
-
-    fn heads_east(&self) -> bool {
-        match self {
-            Tile::Pipe(Pipe::SouthEast)
-            | Tile::Pipe(Pipe::NorthEast)
-            | Tile::Pipe(Pipe::Horizontal)
-            | Tile::Animal => true,
-            _ => false,
-        }
-    }
-
-    fn heads_west(&self) -> bool {
-        match self {
-            Tile::Pipe(Pipe::NorthWest)
-            | Tile::Pipe(Pipe::SouthWest)
-            | Tile::Pipe(Pipe::Horizontal)
-            | Tile::Animal => true,
-            _ => false,
-        }
-    }
-
     fn from(char: char) -> Self {
         match char {
             'S' => Self::Animal,
@@ -365,7 +337,7 @@ impl Tile {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 enum Pipe {
     Vertical,
     Horizontal,
@@ -376,40 +348,48 @@ enum Pipe {
 }
 
 impl Pipe {
-    fn tripled(&self) -> [[bool; 3]; 3] {
+    const ALL: [Pipe; 6] = [
+        Pipe::Vertical,
+        Pipe::Horizontal,
+        Pipe::NorthEast,
+        Pipe::NorthWest,
+        Pipe::SouthWest,
+        Pipe::SouthEast,
+    ];
+
+    fn exits(&self) -> [Dir; 2] {
+        match self {
+            Pipe::Vertical => [Dir::North, Dir::South],
+            Pipe::Horizontal => [Dir::East, Dir::West],
+            Pipe::NorthEast => [Dir::North, Dir::East],
+            Pipe::NorthWest => [Dir::North, Dir::West],
+            Pipe::SouthWest => [Dir::South, Dir::West],
+            Pipe::SouthEast => [Dir::South, Dir::East],
+        }
+    }
+
+    fn glyph(&self) -> char {
         match self {
-            Pipe::Vertical => [
-                [false, true, false],
-                [false, true, false],
-                [false, true, false],
-            ],
-            Pipe::Horizontal => [
-                [false, false, false],
-                [true, true, true],
-                [false, false, false],
-            ],
-            Pipe::NorthEast => [
-                [false, true, false],
-                [false, true, true],
-                [false, false, false],
-            ],
-            Pipe::NorthWest => [
-                [false, true, false],
-                [true, true, false],
-                [false, false, false],
-            ],
-            Pipe::SouthEast => [
-                [false, false, false],
-                [false, true, true],
-                [false, true, false],
-            ],
-            Pipe::SouthWest => [
-                [false, false, false],
-                [true, true, false],
-                [false, true, false],
-            ],
+            Pipe::Vertical => '│',
+            Pipe::Horizontal => '─',
+            Pipe::NorthEast => '└',
+            Pipe::NorthWest => '┘',
+            Pipe::SouthWest => '┐',
+            Pipe::SouthEast => '┌',
         }
     }
+
+    fn heavy_glyph(&self) -> char {
+        match self {
+            Pipe::Vertical => '┃',
+            Pipe::Horizontal => '━',
+            Pipe::NorthEast => '┗',
+            Pipe::NorthWest => '┛',
+            Pipe::SouthWest => '┓',
+            Pipe::SouthEast => '┏',
+        }
+    }
+
     fn from(char: char) -> Self {
         use Pipe::*;
         match char {
@@ -457,6 +437,117 @@ L7JLJL-JLJLJL--JLJ.L",
     fn test_animal_type() {
         let input = Day10::test_input();
         let diagram: Diagram = input.as_str().parse().unwrap();
-        assert_eq!(diagram.get_animal_type(), Pipe::SouthEast)
+        assert_eq!(diagram.get_animal_type(), Ok(Pipe::SouthEast))
+    }
+
+    #[test]
+    fn test_loop_report() {
+        let input = Day10::test_input();
+        let diagram: Diagram = input.as_str().parse().unwrap();
+        let report = diagram.analyze().unwrap();
+        assert_eq!(report.length, 16);
+        assert_eq!(report.distance, 8);
+        assert_eq!(report.farthest, Pos(2, 4));
+        assert_eq!(report.enclosed, 1);
+        assert_eq!(report.enclosed_tiles().collect::<Vec<_>>(), vec![Pos(2, 2)]);
+    }
+
+    #[test]
+    fn test_enclosed_tiles() {
+        let input = String::from(
+            "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+        );
+        let diagram: Diagram = input.parse().unwrap();
+        let report = diagram.analyze().unwrap();
+        assert_eq!(report.enclosed, 4);
+        let tiles: Vec<Pos> = report.enclosed_tiles().collect();
+        assert_eq!(tiles, vec![Pos(6, 2), Pos(6, 3), Pos(6, 7), Pos(6, 8)]);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let counter_clockwise: Diagram = "S7\nLJ".parse().unwrap();
+        let report = counter_clockwise.analyze().unwrap();
+        assert_eq!(report.length, 4);
+        assert_eq!(report.enclosed, 0);
+        // S connects south first, so the walk goes down, right, up, left
+        assert_eq!(report.orientation, Orientation::CounterClockwise);
+
+        let mirrored: Diagram = "FS\nLJ".parse().unwrap();
+        assert_eq!(
+            mirrored.analyze().unwrap().orientation,
+            Orientation::Clockwise
+        );
+    }
+
+    #[test]
+    fn test_render_glyphs() {
+        let input = Day10::test_input();
+        let diagram: Diagram = input.as_str().parse().unwrap();
+        assert_eq!(
+            diagram.render(RenderStyle::Glyphs).unwrap(),
+            "··┌┐·\n·┌┘│·\n┏┘●└┐\n│┌──┘\n└┘···\n"
+        );
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let diagram: Diagram = "-S7\n.LJ".parse().unwrap();
+        assert_eq!(
+            diagram.render(RenderStyle::Ansi).unwrap(),
+            "\x1b[2m─\x1b[0m\x1b[1;31m┌\x1b[0m\x1b[32m┐\x1b[0m\n\
+             \x1b[2m.\x1b[0m\x1b[32m└\x1b[0m\x1b[32m┘\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn test_start_on_edges() {
+        let cases = [
+            ("S-7\n|.|\nL-J", Pipe::SouthEast),
+            ("F-S\n|.|\nL-J", Pipe::SouthWest),
+            ("F-7\nS.|\nL-J", Pipe::Vertical),
+            ("F-7\n|.|\nL-S", Pipe::NorthWest),
+        ];
+        for (input, pipe) in cases {
+            let diagram: Diagram = input.parse().unwrap();
+            assert_eq!(diagram.get_animal_type(), Ok(pipe), "{input}");
+            assert_eq!(diagram.analyze().unwrap().length, 8);
+        }
+    }
+
+    #[test]
+    fn test_start_ignores_junk() {
+        // both the `-` east of S and the `|` below it point back at S, but
+        // they lead off the map rather than round a loop
+        let diagram: Diagram = "F-7.\n|.|.\nL-S-\n..|.".parse().unwrap();
+        assert_eq!(diagram.get_animal_type(), Ok(Pipe::NorthWest));
+        assert_eq!(diagram.analyze().unwrap().enclosed, 1);
+    }
+
+    #[test]
+    fn test_start_errors() {
+        let diagram: Diagram = "F7\nLJ".parse().unwrap();
+        assert_eq!(diagram.get_animal_type(), Err(StartError::Missing));
+
+        let diagram: Diagram = "S-7\n...".parse().unwrap();
+        assert_eq!(diagram.get_animal_type(), Err(StartError::NoLoop));
+        assert_eq!(diagram.analyze().err(), Some(StartError::NoLoop));
+
+        let diagram: Diagram = "F7.\nLS7\n.LJ".parse().unwrap();
+        assert_eq!(
+            diagram.get_animal_type(),
+            Err(StartError::Ambiguous(vec![
+                Pipe::NorthWest,
+                Pipe::SouthEast
+            ]))
+        );
     }
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, str::FromStr};
+use std::str::FromStr;
 
 use super::Solution;
 
@@ -22,106 +22,134 @@ impl Solution for Day11 {
 
     fn solve_part_1(input: String) -> String {
         let galaxy = Galaxy::from_str(&input).unwrap();
-        galaxy.dist(2, 1).to_string()
+        galaxy.dist(Expansion::uniform(2)).to_string()
     }
 
     fn solve_part_2(input: String) -> String {
         let galaxy = Galaxy::from_str(&input).unwrap();
-        galaxy.dist(1_000_000, 1).to_string()
+        galaxy.dist(Expansion::uniform(1_000_000)).to_string()
     }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct Star(usize, usize);
 
+// how many rows/cols each empty row/col becomes; 0 collapses them
+#[derive(Copy, Clone, Debug)]
+struct Expansion {
+    rows: usize,
+    cols: usize,
+}
+
+impl Expansion {
+    fn uniform(factor: usize) -> Self {
+        Self {
+            rows: factor,
+            cols: factor,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Galaxy {
     stars: Vec<Star>,
-    empty_rows: HashSet<usize>,
-    empty_cols: HashSet<usize>,
+    // empty_rows_before[r]: empty rows strictly above r
+    empty_rows_before: Vec<usize>,
+    empty_cols_before: Vec<usize>,
 }
 
 impl Galaxy {
-    fn dist_btw(&self, from: Star, to: Star, expansion_rate: usize, num_expansion: u32) -> usize {
-        let Star(sr, sc) = from;
-        let Star(er, ec) = to;
-
-        let (sr, er) = if sr < er { (sr, er) } else { (er, sr) };
-        let (sc, ec) = if sc < ec { (sc, ec) } else { (ec, sc) };
+    // empty_before[x] <= x, so taking the empty lines out first can't underflow
+    fn expand(&self, expansion: Expansion) -> Universe {
+        let stars = self
+            .stars
+            .iter()
+            .map(|&Star(r, c)| {
+                Star(
+                    r - self.empty_rows_before[r] + self.empty_rows_before[r] * expansion.rows,
+                    c - self.empty_cols_before[c] + self.empty_cols_before[c] * expansion.cols,
+                )
+            })
+            .collect();
+        Universe { stars }
+    }
 
-        let mut count = 0;
+    fn dist(&self, expansion: Expansion) -> usize {
+        self.expand(expansion).total()
+    }
+}
 
-        for r in sr..er {
-            if let Some(_) = self.empty_rows.get(&r) {
-                count += expansion_rate.pow(num_expansion);
-            } else {
-                count += 1;
-            }
-        }
-        for c in sc..ec {
-            if let Some(_) = self.empty_cols.get(&c) {
-                count += expansion_rate.pow(num_expansion);
-            } else {
-                count += 1;
-            }
-        }
+// expanded stars, same order as Galaxy::stars
+#[derive(Debug)]
+struct Universe {
+    stars: Vec<Star>,
+}
 
-        count
+impl Universe {
+    fn distance(&self, i: usize, j: usize) -> usize {
+        let (Star(r1, c1), Star(r2, c2)) = (self.stars[i], self.stars[j]);
+        r1.abs_diff(r2) + c1.abs_diff(c2)
     }
-    fn dist(&self, expansion_rate: usize, num_expansion: u32) -> usize {
-        let mut count = 0;
-        let num_stars = self.stars.len();
-
-        for i in 0..num_stars {
-            let from = self.stars[i];
-            for j in (i + 1)..num_stars {
-                let to = self.stars[j];
-                count += self.dist_btw(from, to, expansion_rate, num_expansion);
+
+    // per axis, sorted: the k-th coordinate adds x * k minus the k before it
+    fn total(&self) -> usize {
+        let axis_total = |mut xs: Vec<usize>| {
+            xs.sort_unstable();
+            let mut prefix = 0;
+            let mut total = 0;
+            for (k, x) in xs.into_iter().enumerate() {
+                total += x * k - prefix;
+                prefix += x;
             }
-        }
+            total
+        };
+        axis_total(self.stars.iter().map(|s| s.0).collect())
+            + axis_total(self.stars.iter().map(|s| s.1).collect())
+    }
 
-        count
+    // (index, distance) of the k nearest to i, ties by index
+    fn nearest(&self, i: usize, k: usize) -> Vec<(usize, usize)> {
+        let mut others: Vec<(usize, usize)> = (0..self.stars.len())
+            .filter(|&j| j != i)
+            .map(|j| (j, self.distance(i, j)))
+            .collect();
+        others.sort_unstable_by_key(|&(j, d)| (d, j));
+        others.truncate(k);
+        others
     }
 }
 
 impl FromStr for Galaxy {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut stars = vec![];
-        let mut empty_rows = HashSet::new();
-        let mut empty_cols = HashSet::new();
-
         let chars: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
+        let width = chars.iter().map(|row| row.len()).max().unwrap_or(0);
 
-        let size = Star(chars.len(), chars[1].len());
-        for r in 0..size.0 {
-            let mut had_star = false;
-            for c in 0..size.1 {
-                if chars[r][c] == '#' {
+        let mut stars = vec![];
+        let mut row_has_star = vec![false; chars.len()];
+        let mut col_has_star = vec![false; width];
+        for (r, row) in chars.iter().enumerate() {
+            for (c, &ch) in row.iter().enumerate() {
+                if ch == '#' {
                     stars.push(Star(r, c));
-                    had_star = true;
+                    row_has_star[r] = true;
+                    col_has_star[c] = true;
                 }
             }
-            if !had_star {
-                empty_rows.insert(r);
-            }
         }
-        for c in 0..size.1 {
-            let mut had_star = false;
-            for r in 0..size.0 {
-                if chars[r][c] == '#' {
-                    had_star = true;
-                }
-            }
-            if !had_star {
-                empty_cols.insert(c);
+
+        let empty_before = |has_star: Vec<bool>| {
+            let mut before = vec![0; has_star.len() + 1];
+            for (i, has) in has_star.into_iter().enumerate() {
+                before[i + 1] = before[i] + usize::from(!has);
             }
-        }
+            before
+        };
 
         Ok(Self {
             stars,
-            empty_cols,
-            empty_rows,
+            empty_rows_before: empty_before(row_has_star),
+            empty_cols_before: empty_before(col_has_star),
         })
     }
 }
@@ -142,6 +170,65 @@ mod day11_tests {
         let input = Day11::test_input();
         let galaxy = Galaxy::from_str(&input).unwrap();
 
-        assert_eq!(galaxy.dist(10, 1).to_string(), "1030");
+        assert_eq!(galaxy.dist(Expansion::uniform(10)).to_string(), "1030");
+        assert_eq!(galaxy.dist(Expansion::uniform(100)).to_string(), "8410");
+    }
+
+    #[test]
+    fn test_pair_distance() {
+        let input = Day11::test_input();
+        let galaxy = Galaxy::from_str(&input).unwrap();
+        let universe = galaxy.expand(Expansion::uniform(2));
+        assert_eq!(universe.distance(4, 8), 9);
+        assert_eq!(universe.distance(0, 6), 15);
+        assert_eq!(universe.distance(2, 5), 17);
+        assert_eq!(universe.distance(7, 8), 5);
+    }
+
+    #[test]
+    fn test_per_axis_expansion() {
+        let input = Day11::test_input();
+        let galaxy = Galaxy::from_str(&input).unwrap();
+        for (rows, cols) in [(0, 2), (1, 1), (1, 2), (5, 1), (3, 7)] {
+            let universe = galaxy.expand(Expansion { rows, cols });
+            let n = universe.stars.len();
+            let brute: usize = (0..n)
+                .flat_map(|i| (i + 1..n).map(move |j| (i, j)))
+                .map(|(i, j)| universe.distance(i, j))
+                .sum();
+            assert_eq!(universe.total(), brute);
+        }
+        // only the columns grow, so the first and last stars stay 9 rows apart
+        let universe = galaxy.expand(Expansion { rows: 1, cols: 10 });
+        assert_eq!(universe.stars[0], Star(0, 12));
+        assert_eq!(universe.stars[8], Star(9, 13));
+
+        // a factor of 0 removes the empty lines altogether
+        let universe = galaxy.expand(Expansion { rows: 0, cols: 0 });
+        assert_eq!(universe.stars[0], Star(0, 2));
+        assert_eq!(universe.stars[8], Star(7, 3));
+    }
+
+    #[test]
+    fn test_nearest() {
+        let input = Day11::test_input();
+        let galaxy = Galaxy::from_str(&input).unwrap();
+        let universe = galaxy.expand(Expansion::uniform(2));
+        // star 4 is at (6, 1) once expanded; stars 0 and 8 tie at 9, as do 5 and 6 at 12
+        assert_eq!(universe.nearest(4, 4), vec![(2, 5), (7, 6), (3, 8), (0, 9)]);
+        assert_eq!(
+            universe.nearest(4, 8),
+            vec![
+                (2, 5),
+                (7, 6),
+                (3, 8),
+                (0, 9),
+                (8, 9),
+                (5, 12),
+                (6, 12),
+                (1, 13)
+            ]
+        );
+        assert_eq!(universe.nearest(4, 100).len(), 8);
     }
 }
@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-use super::Solution;
+use super::{bignum::BigUint, Solution};
 
 pub struct Day12;
 
@@ -17,38 +15,36 @@ impl Solution for Day12 {
     }
 
     fn solve_part_1(input: String) -> String {
-        input
-            .lines()
-            .map(|line| parse_line(line))
-            .map(|(springs, pattern)| dp(&springs, &pattern))
-            .sum::<usize>()
-            .to_string()
+        total(&input, 1).to_string()
     }
 
     fn solve_part_2(input: String) -> String {
-        input
-            .lines()
-            .map(|line| parse_line(line))
-            .map(|(springs, pattern)| {
-                let mut new_one = springs.clone();
-                for _ in 0..4 {
-                    new_one.push(Spring::Unknown);
-                    new_one.append(&mut springs.clone());
-                }
-                (
-                    new_one,
-                    (0..5)
-                        .map(|_| pattern.clone())
-                        .flatten()
-                        .collect::<Vec<_>>(),
-                )
-            })
-            .map(|(springs, pattern)| dp(&springs, &pattern))
-            .sum::<usize>()
-            .to_string()
+        total(&input, 5).to_string()
     }
 }
 
+fn total(input: &str, factor: usize) -> BigUint {
+    input
+        .lines()
+        .map(parse_line)
+        .map(|(springs, pattern)| unfold(&springs, &pattern, factor))
+        .fold(BigUint::default(), |acc, (springs, pattern)| {
+            &acc + &count(&springs, &pattern)
+        })
+}
+
+// the row repeated factor times, springs joined by a ?
+fn unfold(springs: &[Spring], pattern: &[usize], factor: usize) -> (Vec<Spring>, Vec<usize>) {
+    let mut unfolded = vec![];
+    for k in 0..factor {
+        if k > 0 {
+            unfolded.push(Spring::Unknown);
+        }
+        unfolded.extend_from_slice(springs);
+    }
+    (unfolded, pattern.repeat(factor))
+}
+
 fn parse_line(line: &str) -> (Vec<Spring>, Vec<usize>) {
     let mut words = line.trim().split_whitespace();
     (
@@ -69,63 +65,119 @@ fn parse_line(line: &str) -> (Vec<Spring>, Vec<usize>) {
     )
 }
 
-fn dp(springs: &[Spring], pattern: &[usize]) -> usize {
-    let mut cache: HashMap<(usize, usize, usize), usize> = HashMap::new();
-
-    fn inner(
-        i: usize,
-        n: usize,
-        b: usize,
-        springs: &[Spring],
-        pattern: &[usize],
-        cache: &mut HashMap<(usize, usize, usize), usize>,
-    ) -> usize {
-        if let Some(ans) = cache.get(&(i, n, b)) {
-            return *ans;
+// table[i][n]: groups n.. placed in springs[i..], filled from the back
+fn table<T: Clone + Default>(
+    springs: &[Spring],
+    pattern: &[usize],
+    done: T,
+    add: impl Fn(&T, &T) -> T,
+) -> Vec<Vec<T>> {
+    let len = springs.len();
+    let groups = pattern.len();
+
+    let run = runs(springs);
+    let mut table = vec![vec![T::default(); groups + 1]; len + 1];
+    table[len][groups] = done;
+    for i in (0..len).rev() {
+        for n in 0..=groups {
+            let skip = match springs[i] {
+                Spring::Broken => T::default(),
+                _ => table[i + 1][n].clone(),
+            };
+            let place = match pattern.get(n) {
+                Some(&g) if fits(springs, &run, i, g) => table[(i + g + 1).min(len)][n + 1].clone(),
+                _ => T::default(),
+            };
+            table[i][n] = add(&skip, &place);
         }
+    }
+    table
+}
 
-        if i == springs.len() {
-            if n == pattern.len() && b == 0 {
-                return 1;
-            } else if let Some(&last) = pattern.last() {
-                if last == b && n == pattern.len() - 1 {
-                    return 1;
-                }
-            }
-            return 0;
+// how many springs from i on could all be broken
+fn runs(springs: &[Spring]) -> Vec<usize> {
+    let mut run = vec![0; springs.len() + 1];
+    for i in (0..springs.len()).rev() {
+        if springs[i] != Spring::Operational {
+            run[i] = run[i + 1] + 1;
         }
-        let mut ans = 0;
-
-        match springs[i] {
-            Spring::Broken => {}
-            _ => {
-                if b == 0 {
-                    ans += inner(i + 1, n, 0, springs, pattern, cache);
-                } else {
-                    if n == pattern.len() {
-                        return 0;
-                    }
-                    if b == pattern[n] {
-                        ans += inner(i + 1, n + 1, 0, springs, pattern, cache);
+    }
+    run
+}
+
+// a group of g can start at i and isn't followed by a #
+fn fits(springs: &[Spring], run: &[usize], i: usize, g: usize) -> bool {
+    run[i] >= g && springs.get(i + g) != Some(&Spring::Broken)
+}
+
+fn count(springs: &[Spring], pattern: &[usize]) -> BigUint {
+    table(springs, pattern, BigUint::from(1u8), |a, b| a + b)[0][0].clone()
+}
+
+// up to limit arrangements, . before #, dead branches pruned by the table
+fn arrangements<'a>(springs: &'a [Spring], pattern: &'a [usize], limit: usize) -> Arrangements<'a> {
+    let feasible = table(springs, pattern, true, |a, b| *a || *b);
+    let stack = if feasible[0][0] {
+        vec![(0, 0, vec![])]
+    } else {
+        vec![]
+    };
+    Arrangements {
+        springs,
+        pattern,
+        run: runs(springs),
+        feasible,
+        stack,
+        limit,
+    }
+}
+
+struct Arrangements<'a> {
+    springs: &'a [Spring],
+    pattern: &'a [usize],
+    run: Vec<usize>,
+    feasible: Vec<Vec<bool>>,
+    stack: Vec<(usize, usize, Vec<Spring>)>,
+    limit: usize,
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<Spring>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.limit == 0 {
+            return None;
+        }
+        let len = self.springs.len();
+
+        while let Some((i, n, prefix)) = self.stack.pop() {
+            if i == len {
+                self.limit -= 1;
+                return Some(prefix);
+            }
+            // pushed first so the `.` branch below is explored first
+            if let Some(&g) = self.pattern.get(n) {
+                let next = (i + g + 1).min(len);
+                if fits(self.springs, &self.run, i, g) && self.feasible[next][n + 1] {
+                    let mut placed = prefix.clone();
+                    placed.extend(std::iter::repeat_n(Spring::Broken, g));
+                    if next > i + g {
+                        placed.push(Spring::Operational);
                     }
+                    self.stack.push((next, n + 1, placed));
                 }
             }
-        }
-
-        match springs[i] {
-            Spring::Operational => {}
-            _ => {
-                ans += inner(i + 1, n, b + 1, springs, pattern, cache);
+            if self.springs[i] != Spring::Broken && self.feasible[i + 1][n] {
+                let mut skipped = prefix;
+                skipped.push(Spring::Operational);
+                self.stack.push((i + 1, n, skipped));
             }
         }
-
-        cache.insert((i, n, b), ans);
-        ans
+        None
     }
-    inner(0, 0, 0, springs, pattern, &mut cache)
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 enum Spring {
     Operational,
     Broken,
@@ -141,6 +193,14 @@ impl Spring {
             _ => unreachable!(),
         }
     }
+
+    fn char(&self) -> char {
+        match self {
+            Self::Operational => '.',
+            Self::Broken => '#',
+            Self::Unknown => '?',
+        }
+    }
 }
 
 #[cfg(test)]
@@ -161,4 +221,77 @@ mod day12_tests {
 
         assert_eq!(ans, "525152");
     }
+
+    #[test]
+    fn test_unfold_factor() {
+        let input = Day12::test_input();
+        let counts: Vec<String> = input
+            .lines()
+            .map(parse_line)
+            .map(|(springs, pattern)| {
+                let (springs, pattern) = unfold(&springs, &pattern, 5);
+                count(&springs, &pattern).to_string()
+            })
+            .collect();
+        assert_eq!(counts, ["1", "16384", "1", "16", "2500", "506250"]);
+        let enumerated: usize = input
+            .lines()
+            .map(parse_line)
+            .map(|(springs, pattern)| {
+                let (springs, pattern) = unfold(&springs, &pattern, 2);
+                arrangements(&springs, &pattern, usize::MAX).count()
+            })
+            .sum();
+        assert_eq!(total(&input, 2).to_string(), enumerated.to_string());
+    }
+
+    #[test]
+    fn test_large_counts() {
+        // same table over u128 agrees while it still fits
+        let (springs, pattern) = parse_line("?###???????? 3,2,1");
+        for factor in 1..=12 {
+            let (springs, pattern) = unfold(&springs, &pattern, factor);
+            let small = table(&springs, &pattern, 1u128, |a, b| a + b)[0][0];
+            assert_eq!(count(&springs, &pattern).to_string(), small.to_string());
+        }
+
+        // "??" unfolded f times is 3f-1 unknowns holding f single springs,
+        // which is C(2f, f) ways; C(140, 70) is past u128::MAX
+        let (springs, pattern) = parse_line("?? 1");
+        let (springs, pattern) = unfold(&springs, &pattern, 70);
+        let mut pascal = vec![BigUint::from(1u8)];
+        for _ in 0..140 {
+            let mut next = vec![BigUint::from(1u8)];
+            next.extend(pascal.windows(2).map(|w| &w[0] + &w[1]));
+            next.push(BigUint::from(1u8));
+            pascal = next;
+        }
+        let big = count(&springs, &pattern);
+        assert!(big > BigUint::from(u128::MAX));
+        assert_eq!(big, pascal[70]);
+        assert_eq!(big.to_string(), "93820969697840041204785894580506297666600");
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let (springs, pattern) = parse_line(".??..??...?##. 1,1,3");
+        let rows: Vec<String> = arrangements(&springs, &pattern, usize::MAX)
+            .map(|row| row.iter().map(Spring::char).collect())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "..#...#...###.",
+                "..#..#....###.",
+                ".#....#...###.",
+                ".#...#....###.",
+            ]
+        );
+        assert_eq!(arrangements(&springs, &pattern, 2).count(), 2);
+
+        let (springs, pattern) = parse_line("?###???????? 3,2,1");
+        assert_eq!(arrangements(&springs, &pattern, usize::MAX).count(), 10);
+        let (springs, pattern) = parse_line("#.# 2");
+        assert_eq!(arrangements(&springs, &pattern, usize::MAX).count(), 0);
+    }
 }
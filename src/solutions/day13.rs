@@ -26,109 +26,112 @@ impl Solution for Day13 {
     }
 
     fn solve_part_1(input: String) -> String {
-        let mirrors: Vec<Mirror> = input
-            .split("\n\n")
-            .map(|str| str.parse().unwrap())
-            .collect();
-        mirrors
-            .iter()
-            .map(|mirror| {
-                let mut ans = 0;
-                if let Some(r) = mirror.row_symmetry() {
-                    ans += 100 * r;
-                }
-                if let Some(c) = mirror.col_symmetry() {
-                    ans += c;
-                }
-                ans
-            })
-            .sum::<usize>()
-            .to_string()
+        summarize(&input, 0).to_string()
     }
 
     fn solve_part_2(input: String) -> String {
-        let mirrors: Vec<Mirror> = input
-            .split("\n\n")
-            .map(|str| str.parse().unwrap())
-            .collect();
-        mirrors
-            .iter()
-            .map(|mirror| {
-                let mut ans = 0;
-                if let Some(r) = mirror.row_smudged_symmetry() {
-                    ans += 100 * r;
-                }
-                if let Some(c) = mirror.col_smudged_symmetry() {
-                    ans += c;
-                }
-                ans
-            })
-            .sum::<usize>()
-            .to_string()
+        summarize(&input, 1).to_string()
     }
 }
 
+// notes for the reflections with exactly this many smudges
+fn summarize(input: &str, smudges: u32) -> usize {
+    input
+        .split("\n\n")
+        .map(|str| str.parse::<Mirror>().unwrap())
+        .flat_map(|mirror| mirror.reflections(smudges))
+        .filter(|reflection| reflection.smudges == smudges)
+        .map(|reflection| reflection.summary())
+        .sum()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Axis {
+    // between two rows
+    Horizontal,
+    // between two columns
+    Vertical,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Reflection {
+    axis: Axis,
+    // rows above / columns left of the line
+    line: usize,
+    // tiles that differ from their mirror image
+    smudges: u32,
+}
+
+impl Reflection {
+    fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Horizontal => 100 * self.line,
+            Axis::Vertical => self.line,
+        }
+    }
+}
+
+// # as set bits, by row and by column, so lines compare with one xor
 #[derive(Debug)]
 struct Mirror {
-    chars: Vec<Vec<char>>,
+    rows: Vec<u64>,
+    cols: Vec<u64>,
 }
 
 impl Mirror {
-    fn transposed(&self) -> Mirror {
-        Self {
-            chars: (0..self.chars[0].len())
-                .map(|c| self.chars.iter().map(|row| row[c]).collect())
-                .collect(),
-        }
+    // every line with at most max_smudges mismatches, horizontal first
+    fn reflections(&self, max_smudges: u32) -> Vec<Reflection> {
+        let horizontal = Self::lines(&self.rows, max_smudges).map(|(line, smudges)| Reflection {
+            axis: Axis::Horizontal,
+            line,
+            smudges,
+        });
+        let vertical = Self::lines(&self.cols, max_smudges).map(|(line, smudges)| Reflection {
+            axis: Axis::Vertical,
+            line,
+            smudges,
+        });
+        horizontal.chain(vertical).collect()
     }
 
-    fn row_smudged_symmetry(&self) -> Option<usize> {
-        for r in 1..self.chars.len() {
+    fn lines(masks: &[u64], max_smudges: u32) -> impl Iterator<Item = (usize, u32)> + '_ {
+        (1..masks.len()).filter_map(move |line| {
             let mut smudges = 0;
-            for (row1, row2) in self.chars[r..].iter().zip(self.chars[0..r].iter().rev()) {
-                for (c1, c2) in row1.iter().zip(row2.iter()) {
-                    if c1 != c2 {
-                        smudges += 1;
-                    }
+            for (a, b) in masks[line..].iter().zip(masks[..line].iter().rev()) {
+                smudges += (a ^ b).count_ones();
+                if smudges > max_smudges {
+                    return None;
                 }
             }
-            if smudges == 1 {
-                return Some(r);
-            }
-        }
-        None
-    }
-
-    fn col_smudged_symmetry(&self) -> Option<usize> {
-        self.transposed().row_smudged_symmetry()
-    }
-
-    fn row_symmetry(&self) -> Option<usize> {
-        for r in 1..self.chars.len() {
-            let mut symm = true;
-            for (row1, row2) in self.chars[r..].iter().zip(self.chars[0..r].iter().rev()) {
-                if row1 != row2 {
-                    symm = false;
-                    break;
-                }
-            }
-            if symm {
-                return Some(r);
-            }
-        }
-        None
-    }
-
-    fn col_symmetry(&self) -> Option<usize> {
-        self.transposed().row_symmetry()
+            Some((line, smudges))
+        })
     }
 }
 
 impl FromStr for Mirror {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let chars = s.lines().map(|line| line.chars().collect()).collect();
-        Ok(Self { chars })
+        let chars: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
+        let width = chars.first().map_or(0, |row| row.len());
+        if chars.len() > 64 || width > 64 || chars.iter().any(|row| row.len() != width) {
+            return Err(());
+        }
+
+        let mut rows = vec![0; chars.len()];
+        let mut cols = vec![0; width];
+        for (r, row) in chars.iter().enumerate() {
+            for (c, &ch) in row.iter().enumerate() {
+                match ch {
+                    '#' => {
+                        rows[r] |= 1 << c;
+                        cols[c] |= 1 << r;
+                    }
+                    '.' => {}
+                    _ => return Err(()),
+                }
+            }
+        }
+        Ok(Self { rows, cols })
     }
 }
 
@@ -149,4 +152,72 @@ mod day13_tests {
         let ans = Day13::solve_part_2(input);
         assert_eq!(ans, "400");
     }
+
+    #[test]
+    fn test_reflections() {
+        let input = Day13::test_input();
+        let mirrors: Vec<Mirror> = input.split("\n\n").map(|s| s.parse().unwrap()).collect();
+
+        let reflection = |axis, line, smudges| Reflection {
+            axis,
+            line,
+            smudges,
+        };
+        assert_eq!(
+            mirrors[0].reflections(1),
+            [
+                reflection(Axis::Horizontal, 3, 1),
+                reflection(Axis::Vertical, 5, 0)
+            ]
+        );
+        assert_eq!(
+            mirrors[1].reflections(1),
+            [
+                reflection(Axis::Horizontal, 1, 1),
+                reflection(Axis::Horizontal, 4, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_smudge_counts() {
+        let input = Day13::test_input();
+        for pattern in input.split("\n\n") {
+            let mirror: Mirror = pattern.parse().unwrap();
+            let chars: Vec<Vec<char>> = pattern.lines().map(|l| l.chars().collect()).collect();
+            let (h, w) = (chars.len(), chars[0].len());
+
+            // every line shows up once the budget covers the whole pattern
+            let all = mirror.reflections((h * w) as u32);
+            assert_eq!(all.len(), h - 1 + w - 1);
+            for reflection in all {
+                let line = reflection.line;
+                let mut smudges = 0;
+                for r in 0..h {
+                    for c in 0..w {
+                        let (mr, mc) = match reflection.axis {
+                            Axis::Horizontal => ((2 * line).wrapping_sub(r + 1), c),
+                            Axis::Vertical => (r, (2 * line).wrapping_sub(c + 1)),
+                        };
+                        let below = match reflection.axis {
+                            Axis::Horizontal => r >= line,
+                            Axis::Vertical => c >= line,
+                        };
+                        if below && mr < h && mc < w && chars[r][c] != chars[mr][mc] {
+                            smudges += 1;
+                        }
+                    }
+                }
+                assert_eq!(reflection.smudges, smudges, "{reflection:?}");
+                assert!(mirror.reflections(smudges).contains(&reflection));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!("#.\n#".parse::<Mirror>().is_err());
+        assert!("#x".parse::<Mirror>().is_err());
+        assert!("#".repeat(65).parse::<Mirror>().is_err());
+    }
 }
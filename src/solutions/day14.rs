@@ -1,4 +1,8 @@
-use std::{collections::HashMap, hash::Hash, str::FromStr};
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
 
 use super::Solution;
 
@@ -22,199 +26,194 @@ impl Solution for Day14 {
 
     fn solve_part_1(input: String) -> String {
         let mut platform: Platform = input.parse().unwrap();
-        platform.tilt_to_north();
-        // println!("{}", platform.to_string());
-        platform.total_load().to_string()
+        platform.tilt(Direction::North);
+        platform.load(Direction::North).to_string()
     }
 
     fn solve_part_2(input: String) -> String {
-        let mut pattern: HashMap<Platform, usize> = HashMap::new();
         let mut platform: Platform = input.parse().unwrap();
-
-        let max_cycle = 1_000_000_000;
-
-        for i in 1..=max_cycle {
-            platform.cycle();
-            if let Some(n) = pattern.get(&platform) {
-                let cycle = i - n;
-                let rem = (max_cycle - n) % cycle;
-
-                let platform_at_rem = pattern.iter().find(|(_, v)| **v == n + rem).unwrap().0;
-
-                return platform_at_rem.total_load().to_string();
-            } else {
-                pattern.insert(platform.clone(), i);
-            }
-        }
-        String::from("0")
+        platform.run(&SPIN_CYCLE, 1_000_000_000);
+        platform.load(Direction::North).to_string()
     }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone)]
-enum Rock {
-    Empty,
-    Rounded,
-    Cube,
-}
+const SPIN_CYCLE: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::South,
+    Direction::East,
+];
 
-impl Rock {
-    fn from(char: char) -> Self {
-        match char {
-            '.' => Self::Empty,
-            '#' => Self::Cube,
-            'O' => Self::Rounded,
-            _ => unreachable!(),
-        }
-    }
+#[derive(Debug, PartialEq, Copy, Clone)]
+enum Direction {
+    North,
+    West,
+    South,
+    East,
 }
 
-#[derive(Debug, Clone)]
+// bitboards: bit c of row r is (r, c); cubes never move so they're kept both ways
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Platform {
-    rocks: Vec<Vec<Rock>>,
+    height: usize,
+    width: usize,
+    rounded: Vec<u128>,
+    cube_rows: Vec<u128>,
+    cube_cols: Vec<u128>,
 }
 
-impl Hash for Platform {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        self.to_string().hash(state)
-    }
-}
-
-impl PartialEq for Platform {
-    fn eq(&self, other: &Self) -> bool {
-        self.rocks == other.rocks
-    }
-}
-impl Eq for Platform {}
-
-impl ToString for Platform {
-    fn to_string(&self) -> String {
-        self.rocks
+impl Platform {
+    // each rounded rock counts its distance from the edge opposite the wall
+    fn load(&self, wall: Direction) -> usize {
+        let (h, w) = (self.height, self.width);
+        self.rounded
             .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|rock| match rock {
-                        Rock::Cube => '#',
-                        Rock::Rounded => 'O',
-                        Rock::Empty => '.',
-                    })
-                    .collect::<String>()
+            .enumerate()
+            .map(|(r, &row)| match wall {
+                Direction::North => (h - r) * row.count_ones() as usize,
+                Direction::South => (r + 1) * row.count_ones() as usize,
+                Direction::West => bits(row).map(|c| w - c).sum(),
+                Direction::East => bits(row).map(|c| c + 1).sum(),
             })
-            .collect::<Vec<_>>()
-            .join("\n")
+            .sum()
     }
-}
 
-impl Platform {
-    fn total_load(&self) -> usize {
-        let mut ans = 0;
-        let (max_r, max_c) = self.size();
-        for r in 0..max_r {
-            for c in 0..max_c {
-                if let Rock::Rounded = &self.rocks[r][c] {
-                    ans += max_r - r;
+    // rows settle in place; north/south go through column bitboards and back
+    fn tilt(&mut self, dir: Direction) {
+        match dir {
+            Direction::West | Direction::East => {
+                for (row, &cubes) in self.rounded.iter_mut().zip(&self.cube_rows) {
+                    *row = settle(*row, cubes, self.width, dir == Direction::West);
                 }
             }
+            Direction::North | Direction::South => {
+                let cols = transpose(&self.rounded, self.width);
+                let cols: Vec<u128> = cols
+                    .into_iter()
+                    .zip(&self.cube_cols)
+                    .map(|(col, &cubes)| settle(col, cubes, self.height, dir == Direction::North))
+                    .collect();
+                self.rounded = transpose(&cols, self.height);
+            }
         }
-        ans
-    }
-    fn size(&self) -> (usize, usize) {
-        (self.rocks.len(), self.rocks[0].len())
-    }
-    fn cycle(&mut self) {
-        // nwse
-        self.tilt_to_north();
-        self.tilt_to_west();
-        self.tilt_to_south();
-        self.tilt_to_east();
     }
 
-    fn tilt_to_north(&mut self) {
-        let (max_r, max_c) = self.size();
-        for r in 1..max_r {
-            for c in 0..max_c {
-                let rock = &self.rocks[r][c];
-                if let Rock::Rounded = rock {
-                    for north in (0..r).rev() {
-                        let rock_north = &self.rocks[north][c];
-                        if let Rock::Empty = rock_north {
-                            self.rocks[north][c] = Rock::Rounded;
-                            self.rocks[north + 1][c] = Rock::Empty;
-                        } else {
-                            break;
-                        }
-                    }
-                }
+    // once a layout repeats, skip straight to where times lands in the cycle
+    fn run(&mut self, sequence: &[Direction], times: usize) {
+        let mut seen: HashMap<Vec<u128>, usize> = HashMap::new();
+        let mut history: Vec<Vec<u128>> = vec![];
+        for i in 0..times {
+            if let Some(&start) = seen.get(&self.rounded) {
+                let period = i - start;
+                self.rounded = history[start + (times - start) % period].clone();
+                return;
             }
-        }
-    }
-    fn tilt_to_south(&mut self) {
-        let (max_r, max_c) = self.size();
-        for r in (0..max_r - 1).rev() {
-            for c in 0..max_c {
-                let rock = &self.rocks[r][c];
-                if let Rock::Rounded = rock {
-                    for south in (r + 1)..max_r {
-                        let rock_south = &self.rocks[south][c];
-                        if let Rock::Empty = rock_south {
-                            self.rocks[south][c] = Rock::Rounded;
-                            self.rocks[south - 1][c] = Rock::Empty;
-                        } else {
-                            break;
-                        }
-                    }
-                }
+            seen.insert(self.rounded.clone(), i);
+            history.push(self.rounded.clone());
+            for &dir in sequence {
+                self.tilt(dir);
             }
         }
     }
-    fn tilt_to_west(&mut self) {
-        let (max_r, max_c) = self.size();
-        for c in 1..max_c {
-            for r in 0..max_r {
-                let rock = &self.rocks[r][c];
-                if let Rock::Rounded = rock {
-                    for west in (0..c).rev() {
-                        let rock_west = &self.rocks[r][west];
-                        if let Rock::Empty = rock_west {
-                            self.rocks[r][west] = Rock::Rounded;
-                            self.rocks[r][west + 1] = Rock::Empty;
-                        } else {
-                            break;
-                        }
-                    }
-                }
-            }
+}
+
+// set bit indices, lowest first
+fn bits(mut mask: u128) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (mask != 0).then(|| {
+            let i = mask.trailing_zeros() as usize;
+            mask &= mask - 1;
+            i
+        })
+    })
+}
+
+// len bits of each line become one line per bit position
+fn transpose(lines: &[u128], len: usize) -> Vec<u128> {
+    let mut out = vec![0; len];
+    for (i, &line) in lines.iter().enumerate() {
+        for j in bits(line) {
+            out[j] |= 1 << i;
         }
     }
+    out
+}
 
-    fn tilt_to_east(&mut self) {
-        let (max_r, max_c) = self.size();
-        for c in (0..max_c - 1).rev() {
-            for r in 0..max_r {
-                let rock = &self.rocks[r][c];
-                if let Rock::Rounded = rock {
-                    for east in c + 1..max_c {
-                        let rock_south = &self.rocks[r][east];
-                        if let Rock::Empty = rock_south {
-                            self.rocks[r][east] = Rock::Rounded;
-                            self.rocks[r][east - 1] = Rock::Empty;
-                        } else {
-                            break;
-                        }
-                    }
-                }
+// each gap between cubes keeps its rock count, packed at the low or high end
+fn settle(rocks: u128, cubes: u128, len: usize, toward_low: bool) -> u128 {
+    let mut out = 0;
+    let mut start = 0;
+    while start < len {
+        let end = (start + (cubes >> start).trailing_zeros() as usize).min(len);
+        let segment = span(start, end);
+        let count = (rocks & segment).count_ones() as usize;
+        out |= if toward_low {
+            span(start, start + count)
+        } else {
+            span(end - count, end)
+        };
+        start = end + 1;
+    }
+    out
+}
+
+// bits from..to set
+fn span(from: usize, to: usize) -> u128 {
+    let upto = |n: usize| if n >= 128 { u128::MAX } else { (1 << n) - 1 };
+    upto(to) & !upto(from)
+}
+
+impl Display for Platform {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (r, (&rounded, &cubes)) in self.rounded.iter().zip(&self.cube_rows).enumerate() {
+            if r > 0 {
+                writeln!(f)?;
+            }
+            for c in 0..self.width {
+                let ch = if rounded >> c & 1 == 1 {
+                    'O'
+                } else if cubes >> c & 1 == 1 {
+                    '#'
+                } else {
+                    '.'
+                };
+                write!(f, "{ch}")?;
             }
         }
+        Ok(())
     }
 }
 
 impl FromStr for Platform {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let rocks = s
-            .lines()
-            .map(|line| line.trim().chars().map(|ch| Rock::from(ch)).collect())
-            .collect();
-        Ok(Self { rocks })
+        let lines: Vec<&str> = s.lines().map(|line| line.trim()).collect();
+        let height = lines.len();
+        let width = lines.first().map_or(0, |line| line.len());
+        if height > 128 || width > 128 || lines.iter().any(|line| line.len() != width) {
+            return Err(());
+        }
+
+        let mut rounded = vec![0; height];
+        let mut cube_rows = vec![0; height];
+        for (r, line) in lines.iter().enumerate() {
+            for (c, ch) in line.chars().enumerate() {
+                match ch {
+                    'O' => rounded[r] |= 1 << c,
+                    '#' => cube_rows[r] |= 1 << c,
+                    '.' => {}
+                    _ => return Err(()),
+                }
+            }
+        }
+        let cube_cols = transpose(&cube_rows, width);
+        Ok(Self {
+            height,
+            width,
+            rounded,
+            cube_rows,
+            cube_cols,
+        })
     }
 }
 
@@ -240,7 +239,7 @@ mod day14_tests {
     fn test_cycles() {
         let input = Day14::test_input();
         let mut platform: Platform = input.parse().unwrap();
-        platform.cycle();
+        platform.run(&SPIN_CYCLE, 1);
         let ans: Platform = ".....#....
         ....#...O#
         ...OO##...
@@ -255,7 +254,7 @@ mod day14_tests {
             .unwrap();
 
         assert_eq!(platform, ans);
-        platform.cycle();
+        platform.run(&SPIN_CYCLE, 1);
         let ans: Platform = ".....#....
         ....#...O#
         .....##...
@@ -270,4 +269,113 @@ mod day14_tests {
             .unwrap();
         assert_eq!(platform, ans);
     }
+
+    // roll one tile at a time until nothing moves
+    fn naive_tilt(grid: &mut [Vec<char>], dir: Direction) {
+        let (dr, dc): (isize, isize) = match dir {
+            Direction::North => (-1, 0),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+            Direction::East => (0, 1),
+        };
+        let (h, w) = (grid.len() as isize, grid[0].len() as isize);
+        let mut moved = true;
+        while moved {
+            moved = false;
+            for r in 0..h {
+                for c in 0..w {
+                    let (nr, nc) = (r + dr, c + dc);
+                    if (0..h).contains(&nr)
+                        && (0..w).contains(&nc)
+                        && grid[r as usize][c as usize] == 'O'
+                        && grid[nr as usize][nc as usize] == '.'
+                    {
+                        grid[r as usize][c as usize] = '.';
+                        grid[nr as usize][nc as usize] = 'O';
+                        moved = true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_grid(input: &str) -> Vec<Vec<char>> {
+        input.lines().map(|l| l.trim().chars().collect()).collect()
+    }
+
+    fn render(grid: &[Vec<char>]) -> String {
+        grid.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_tilt_each_direction() {
+        let input = Day14::test_input();
+        for dir in SPIN_CYCLE {
+            let mut platform: Platform = input.parse().unwrap();
+            let mut grid = parse_grid(&input);
+            platform.tilt(dir);
+            naive_tilt(&mut grid, dir);
+            assert_eq!(platform.to_string(), render(&grid), "{dir:?}");
+        }
+    }
+
+    #[test]
+    fn test_run_any_sequence() {
+        let input = Day14::test_input();
+        let sequence = [Direction::East, Direction::North, Direction::East];
+        for times in [0, 1, 2, 7, 30] {
+            let mut platform: Platform = input.parse().unwrap();
+            let mut grid = parse_grid(&input);
+            platform.run(&sequence, times);
+            for _ in 0..times {
+                for dir in sequence {
+                    naive_tilt(&mut grid, dir);
+                }
+            }
+            assert_eq!(platform.to_string(), render(&grid), "{times}");
+        }
+
+        // the naive layouts settle into a loop well within 100 rounds
+        let sequence = [Direction::South, Direction::West];
+        let mut grid = parse_grid(&input);
+        let mut layouts = vec![render(&grid)];
+        for _ in 0..100 {
+            for dir in sequence {
+                naive_tilt(&mut grid, dir);
+            }
+            layouts.push(render(&grid));
+        }
+        let times: usize = 1_000_000_000_000;
+        let (start, period) = (0..layouts.len())
+            .find_map(|j| {
+                (0..j)
+                    .find(|&i| layouts[i] == layouts[j])
+                    .map(|i| (i, j - i))
+            })
+            .unwrap();
+        let mut platform: Platform = input.parse().unwrap();
+        platform.run(&sequence, times);
+        assert_eq!(
+            platform.to_string(),
+            layouts[start + (times - start) % period]
+        );
+    }
+
+    #[test]
+    fn test_load_any_wall() {
+        let platform: Platform = "O.#\n.O.\n..O".parse().unwrap();
+        assert_eq!(platform.load(Direction::North), 3 + 2 + 1);
+        assert_eq!(platform.load(Direction::South), 1 + 2 + 3);
+        assert_eq!(platform.load(Direction::West), 3 + 2 + 1);
+        assert_eq!(platform.load(Direction::East), 1 + 2 + 3);
+
+        let platform: Platform = "OO.\n...\n#..".parse().unwrap();
+        assert_eq!(platform.load(Direction::North), 6);
+        assert_eq!(platform.load(Direction::South), 2);
+        assert_eq!(platform.load(Direction::West), 5);
+        assert_eq!(platform.load(Direction::East), 3);
+    }
 }
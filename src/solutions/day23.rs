@@ -100,26 +100,22 @@ impl Map {
     fn size(&self) -> (usize, usize) {
         (self.tiles.len(), self.tiles[0].len())
     }
-    fn apply_slope(&self, candidates: Vec<(Pos, usize)>) -> Vec<(Pos, usize)> {
-        candidates
-            .into_iter()
-            .filter_map(|(pos, dist)| {
-                let tile = &self.tiles[pos.0][pos.1];
-                match tile {
-                    Forest => unreachable!(),
-                    Path => Some((pos, dist)),
-                    Slope(dir) => {
-                        let new_pos = match dir {
-                            South => Pos(pos.0 + 1, pos.1),
-                            East => Pos(pos.0, pos.1 + 1),
-                            North => Pos(pos.0 - 1, pos.1),
-                            West => Pos(pos.0, pos.1 - 1),
-                        };
-                        Some((new_pos, dist + 1))
-                    }
-                }
-            })
-            .collect()
+    // whether a single step is allowed when slopes are respected
+    fn can_step(&self, from: Pos, to: Pos) -> bool {
+        let dir = if to.0 > from.0 {
+            South
+        } else if to.0 < from.0 {
+            North
+        } else if to.1 > from.1 {
+            East
+        } else {
+            West
+        };
+        let downhill = |pos: Pos| match self.tiles[pos.0][pos.1] {
+            Slope(slope) => slope == dir,
+            _ => true,
+        };
+        downhill(from) && downhill(to)
     }
     fn next(&self, from: Pos) -> Vec<(Pos, usize)> {
         let Pos(r, c) = from;
@@ -149,51 +145,18 @@ impl Map {
     }
 
     fn part_1(&self) -> usize {
-        let mut distances: Vec<Vec<usize>> = self
-            .tiles
-            .iter()
-            .map(|row| row.iter().map(|_| 0).collect())
-            .collect();
-        let mut visited: Vec<Vec<bool>> = self
-            .tiles
-            .iter()
-            .map(|row| row.iter().map(|_| false).collect())
-            .collect();
-
-        let start = Pos(0, 1);
-        let (max_r, max_c) = self.size();
-        let end = Pos(max_r - 1, max_c - 2);
-
-        fn dfs(
-            pos: Pos,
-            dist: usize,
-            map: &Map,
-            visited: &mut Vec<Vec<bool>>,
-            distances: &mut Vec<Vec<usize>>,
-        ) {
-            if visited[pos.0][pos.1] {
-                return;
-            }
-            visited[pos.0][pos.1] = true;
-
-            if distances[pos.0][pos.1] < dist {
-                distances[pos.0][pos.1] = dist;
-            }
-
-            for (next, add_d) in map.apply_slope(map.next(pos)) {
-                dfs(next, dist + add_d, map, visited, distances);
-            }
-
-            visited[pos.0][pos.1] = false;
-        }
-        dfs(start, 0, &self, &mut visited, &mut distances);
-        distances[end.0][end.1]
+        self.junction_graph(true).longest_route().unwrap().length
     }
 
     fn part_2(&self) -> usize {
+        self.junction_graph(false).longest_route().unwrap().length
+    }
+
+    // with `directed`, corridors can only be walked down their slopes
+    fn junction_graph(&self, directed: bool) -> JunctionGraph {
         let mut nodes: HashMap<Pos, Node> = HashMap::new();
 
-        fn add_node(pos: Pos, map: &Map, nodes: &mut HashMap<Pos, Node>) {
+        fn add_node(pos: Pos, map: &Map, directed: bool, nodes: &mut HashMap<Pos, Node>) {
             let mut visited: Vec<Vec<bool>> = map
                 .tiles
                 .iter()
@@ -225,10 +188,13 @@ impl Map {
                         .push((pos, dist));
                     visited[pos.0][pos.1] = true;
                     if nodes.get(&pos).is_none() {
-                        add_node(pos, map, nodes);
+                        add_node(pos, map, directed, nodes);
                     }
                 } else {
                     for (next_pos, d) in next {
+                        if directed && !map.can_step(pos, next_pos) {
+                            continue;
+                        }
                         if !visited[next_pos.0][next_pos.1] {
                             visited[next_pos.0][next_pos.1] = true;
                             stack.push((next_pos, dist + d));
@@ -238,49 +204,199 @@ impl Map {
             }
         }
 
-        add_node(Pos(0, 1), &self, &mut nodes);
+        add_node(Pos(0, 1), self, directed, &mut nodes);
 
         let start = Pos(0, 1);
         let (max_r, max_c) = self.size();
         let end = Pos(max_r - 1, max_c - 2);
 
-        let mut visited: HashMap<Pos, bool> = nodes.keys().map(|pos| (*pos, false)).collect();
-        let mut distances: HashMap<Pos, usize> = nodes.keys().map(|pos| (*pos, 0)).collect();
+        JunctionGraph::from(&nodes, start, end, directed)
+    }
+}
 
-        println!("nodes: {}", nodes.len());
+#[derive(Debug)]
+struct Node {
+    pos: Pos,
+    neighbors: Vec<(Pos, usize)>,
+}
 
-        fn dfs(
-            pos: Pos,
-            dist: usize,
+// junctions renumbered to ids, so a set of visited junctions fits in a u64
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct JunctionGraph {
+    positions: Vec<Pos>,
+    neighbors: Vec<Vec<(usize, usize)>>,
+    start: usize,
+    end: usize,
+    directed: bool,
+}
 
-            nodes: &HashMap<Pos, Node>,
-            visited: &mut HashMap<Pos, bool>,
-            distances: &mut HashMap<Pos, usize>,
-        ) {
-            if *visited.get(&pos).unwrap() {
-                return;
-            }
-            *visited.get_mut(&pos).unwrap() = true;
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Route {
+    length: usize,
+    junctions: Vec<Pos>,
+}
 
-            if distances.get(&pos).unwrap() < &dist {
-                *distances.get_mut(&pos).unwrap() = dist;
-            }
+#[derive(Debug, PartialEq, Eq)]
+enum RouteError {
+    // more junctions than the search can track
+    TooManyJunctions(usize),
+    // no walk from start to end
+    Unreachable,
+}
 
-            for (next, add_d) in nodes.get(&pos).unwrap().neighbors.iter() {
-                dfs(*next, dist + add_d, nodes, visited, distances);
-            }
+impl JunctionGraph {
+    fn from(nodes: &HashMap<Pos, Node>, start: Pos, end: Pos, directed: bool) -> Self {
+        let mut positions: Vec<Pos> = nodes.keys().cloned().collect();
+        positions.sort_by_key(|pos| (pos.0, pos.1));
+        let ids: HashMap<Pos, usize> = positions
+            .iter()
+            .enumerate()
+            .map(|(id, pos)| (*pos, id))
+            .collect();
+        let neighbors = positions
+            .iter()
+            .map(|pos| {
+                nodes[pos]
+                    .neighbors
+                    .iter()
+                    .map(|(next, dist)| (ids[next], *dist))
+                    .collect()
+            })
+            .collect();
+        Self {
+            positions,
+            neighbors,
+            start: ids[&start],
+            end: ids[&end],
+            directed,
+        }
+    }
 
-            *visited.get_mut(&pos).unwrap() = false;
+    // each undirected edge is listed once
+    fn edges(&self) -> Vec<(Pos, Pos, usize)> {
+        self.neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(from, neighbors)| {
+                neighbors
+                    .iter()
+                    .filter(move |(to, _)| self.directed || from < *to)
+                    .map(move |(to, dist)| (self.positions[from], self.positions[*to], *dist))
+            })
+            .collect()
+    }
+
+    fn to_dot(&self) -> String {
+        let name = |pos: &Pos| format!("\"{},{}\"", pos.0, pos.1);
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        let mut lines = vec![format!("{} {{", kind)];
+        lines.push(format!(
+            "    {} [shape=box, label=start];",
+            name(&self.positions[self.start])
+        ));
+        lines.push(format!(
+            "    {} [shape=box, label=end];",
+            name(&self.positions[self.end])
+        ));
+        for (from, to, dist) in self.edges() {
+            lines.push(format!(
+                "    {} {} {} [label={}];",
+                name(&from),
+                arrow,
+                name(&to),
+                dist
+            ));
         }
-        dfs(start, 0, &nodes, &mut visited, &mut distances);
-        *distances.get(&end).unwrap()
+        lines.push("}".to_string());
+        lines.join("\n")
     }
-}
 
-#[derive(Debug)]
-struct Node {
-    pos: Pos,
-    neighbors: Vec<(Pos, usize)>,
+    // one line per junction: "r,c: r,c=dist r,c=dist"
+    fn to_adjacency_list(&self) -> String {
+        self.positions
+            .iter()
+            .zip(self.neighbors.iter())
+            .map(|(pos, neighbors)| {
+                let neighbors: Vec<String> = neighbors
+                    .iter()
+                    .map(|(to, dist)| {
+                        let to = self.positions[*to];
+                        format!("{},{}={}", to.0, to.1, dist)
+                    })
+                    .collect();
+                format!("{},{}: {}", pos.0, pos.1, neighbors.join(" "))
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn longest_route(&self) -> Result<Route, RouteError> {
+        // visited junctions live in a u64
+        if self.positions.len() > 64 {
+            return Err(RouteError::TooManyJunctions(self.positions.len()));
+        }
+
+        // the exit is a dead end: once the only junction leading into it is reached,
+        // the walk has to go straight to the exit, so stop searching there
+        let into_end: Vec<(usize, usize)> = self
+            .neighbors
+            .iter()
+            .enumerate()
+            .filter_map(|(from, neighbors)| {
+                neighbors
+                    .iter()
+                    .find(|(to, _)| *to == self.end)
+                    .map(|(_, dist)| (from, *dist))
+            })
+            .collect();
+        let (target, last_edge) = match into_end[..] {
+            [(before_end, dist)] if before_end != self.start => (before_end, dist),
+            _ => (self.end, 0),
+        };
+
+        let mut best: Option<(usize, Vec<usize>)> = None;
+
+        // (junction, index of the next neighbor to try, length of the edge into it)
+        let mut stack: Vec<(usize, usize, usize)> = vec![(self.start, 0, 0)];
+        let mut visited: u64 = 1 << self.start;
+        let mut dist = 0;
+
+        while let Some((node, next, _)) = stack.last_mut() {
+            let node = *node;
+            if node == target {
+                if best.as_ref().is_none_or(|(length, _)| dist > *length) {
+                    best = Some((dist, stack.iter().map(|(id, _, _)| *id).collect()));
+                }
+            } else if let Some(&(neighbor, d)) = self.neighbors[node].get(*next) {
+                *next += 1;
+                if visited & (1 << neighbor) == 0 {
+                    visited |= 1 << neighbor;
+                    dist += d;
+                    stack.push((neighbor, 0, d));
+                }
+                continue;
+            }
+            // backtrack
+            let (node, _, d) = stack.pop().unwrap();
+            visited &= !(1 << node);
+            dist -= d;
+        }
+
+        let (length, mut ids) = best.ok_or(RouteError::Unreachable)?;
+        if target != self.end {
+            ids.push(self.end);
+        }
+        Ok(Route {
+            length: length + last_edge,
+            junctions: ids.into_iter().map(|id| self.positions[id]).collect(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -300,4 +416,99 @@ mod day23_tests {
         let ans = Day23::solve_part_2(input);
         assert_eq!(ans, "154");
     }
+
+    #[test]
+    fn test_longest_route() {
+        let map: Map = Day23::test_input().parse().unwrap();
+        let graph = map.junction_graph(false);
+        let route = graph.longest_route().unwrap();
+        assert_eq!(route.length, 154);
+        assert_eq!(route.junctions.first(), Some(&Pos(0, 1)));
+        assert_eq!(route.junctions.last(), Some(&Pos(22, 21)));
+
+        let id = |pos: &Pos| graph.positions.iter().position(|p| p == pos).unwrap();
+        let length: usize = route
+            .junctions
+            .windows(2)
+            .map(|pair| {
+                let (from, to) = (id(&pair[0]), id(&pair[1]));
+                graph.neighbors[from]
+                    .iter()
+                    .find(|(next, _)| *next == to)
+                    .unwrap()
+                    .1
+            })
+            .sum();
+        assert_eq!(length, 154);
+    }
+
+    #[test]
+    fn test_directed_graph() {
+        let map: Map = Day23::test_input().parse().unwrap();
+        let directed = map.junction_graph(true);
+        let undirected = map.junction_graph(false);
+        assert_eq!(directed.positions, undirected.positions);
+        // every corridor in the example has a slope on it
+        assert_eq!(directed.edges().len(), undirected.edges().len());
+        assert_eq!(directed.longest_route().unwrap().length, 94);
+    }
+
+    #[test]
+    fn test_export() {
+        let map: Map = "#.###
+        #...#
+        #.#.#
+        #...#
+        ###.#"
+            .parse()
+            .unwrap();
+        let graph = map.junction_graph(false);
+        assert_eq!(
+            graph.to_adjacency_list(),
+            "0,1: 1,1=1
+1,1: 3,3=4 0,1=1
+3,3: 4,3=1 1,1=4
+4,3: 3,3=1"
+        );
+        assert_eq!(
+            graph.to_dot(),
+            "graph {
+    \"0,1\" [shape=box, label=start];
+    \"4,3\" [shape=box, label=end];
+    \"0,1\" -- \"1,1\" [label=1];
+    \"1,1\" -- \"3,3\" [label=4];
+    \"3,3\" -- \"4,3\" [label=1];
+}"
+        );
+        assert_eq!(graph.longest_route().unwrap().length, 6);
+    }
+
+    #[test]
+    fn test_route_errors() {
+        // a straight chain of 65 junctions
+        let chain = |len: usize, linked: bool| JunctionGraph {
+            positions: (0..len).map(|i| Pos(i, 0)).collect(),
+            neighbors: (0..len)
+                .map(|i| {
+                    if linked && i + 1 < len {
+                        vec![(i + 1, 1)]
+                    } else {
+                        vec![]
+                    }
+                })
+                .collect(),
+            start: 0,
+            end: len - 1,
+            directed: true,
+        };
+        assert_eq!(
+            chain(65, true).longest_route(),
+            Err(RouteError::TooManyJunctions(65))
+        );
+        assert_eq!(chain(64, true).longest_route().unwrap().length, 63);
+        assert_eq!(
+            chain(3, false).longest_route(),
+            Err(RouteError::Unreachable)
+        );
+    }
 }
@@ -1,4 +1,5 @@
 use std::{
+    cmp::Ordering,
     collections::HashMap,
     ops::{Add, Div, DivAssign, Sub, SubAssign},
     str::FromStr,
@@ -20,7 +21,7 @@ impl Solution for Day24 {
     }
 
     fn solve_part_1(input: String) -> String {
-        part_1(input, 200000000000000.0, 400000000000000.0).to_string()
+        part_1(input, 200000000000000, 400000000000000).to_string()
     }
 
     fn solve_part_2(input: String) -> String {
@@ -120,31 +121,198 @@ impl Solution for Day24 {
     }
 }
 
-fn part_1(input: String, min: f64, max: f64) -> usize {
-    let mut ans = 0;
-    let hails: Vec<Hail> = input
+fn part_1(input: String, min: i128, max: i128) -> usize {
+    let hails = parse_hails(&input);
+    crossings_in(&hails, &TestArea::square(min, max))
+        .iter()
+        .filter(|(_, _, crossing)| matches!(crossing, Crossing::Future(_)))
+        .count()
+}
+
+fn parse_hails(input: &str) -> Vec<Hail> {
+    input
         .lines()
         .map(|line| line.trim().parse().unwrap())
-        .collect();
-    let len = hails.len();
-    for i in 0..(len - 1) {
-        for j in (i + 1)..len {
-            let lhs = &hails[i];
-            let rhs = &hails[j];
-            if let Some((t0, t1)) = lhs.collides(&rhs) {
-                if !(t0 >= 0.0 && t1 >= 0.0) {
-                    continue;
-                }
-                let (x, y, _) = lhs.at(t0);
-                if min <= x && x <= max && min <= y && y <= max {
-                    ans += 1;
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TestArea {
+    // only x and y are checked, z is ignored as in part 1
+    Rect { min: [i128; 2], max: [i128; 2] },
+    Cuboid { min: [i128; 3], max: [i128; 3] },
+}
+
+impl TestArea {
+    fn square(min: i128, max: i128) -> Self {
+        Self::Rect {
+            min: [min; 2],
+            max: [max; 2],
+        }
+    }
+
+    fn cube(min: i128, max: i128) -> Self {
+        Self::Cuboid {
+            min: [min; 3],
+            max: [max; 3],
+        }
+    }
+
+    fn dims(&self) -> usize {
+        match self {
+            Self::Rect { .. } => 2,
+            Self::Cuboid { .. } => 3,
+        }
+    }
+
+    fn bounds(&self, axis: usize) -> (i128, i128) {
+        match self {
+            Self::Rect { min, max } => (min[axis], max[axis]),
+            Self::Cuboid { min, max } => (min[axis], max[axis]),
+        }
+    }
+
+    fn contains(&self, pos: &[Fraction]) -> bool {
+        pos.iter().enumerate().all(|(axis, x)| {
+            let (min, max) = self.bounds(axis);
+            Fraction::from(min) <= *x && *x <= Fraction::from(max)
+        })
+    }
+
+    // slab test: does the line p + v * t pass through the area at all?
+    fn is_crossed_by(&self, hail: &Hail) -> bool {
+        let mut lo: Option<Fraction> = None;
+        let mut hi: Option<Fraction> = None;
+        for axis in 0..self.dims() {
+            let (min, max) = self.bounds(axis);
+            let p = hail.pos.axis(axis);
+            let v = hail.vel.axis(axis);
+            if v == 0 {
+                if p < min || max < p {
+                    return false;
                 }
+                continue;
+            }
+            let t_min = Fraction::new(min - p, v);
+            let t_max = Fraction::new(max - p, v);
+            let (enter, exit) = if t_min < t_max {
+                (t_min, t_max)
+            } else {
+                (t_max, t_min)
+            };
+            lo = Some(lo.map_or(enter, |lo| lo.max(enter)));
+            hi = Some(hi.map_or(exit, |hi| hi.min(exit)));
+        }
+        match (lo, hi) {
+            (Some(lo), Some(hi)) => lo <= hi,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Intersection {
+    t0: Fraction,
+    t1: Fraction,
+    // only the axes of the test area
+    pos: Vec<Fraction>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Crossing {
+    Future(Intersection),
+    Past(Intersection),
+    Parallel,
+    Coincident,
+    // only in 3D: the xy projections meet, but the paths do not
+    Skew,
+}
+
+// every pair (i, j) whose paths cross inside the area.
+// coincident pairs are listed when their shared path passes through the area.
+fn crossings_in(hails: &[Hail], area: &TestArea) -> Vec<(usize, usize, Crossing)> {
+    let mut ans = vec![];
+    for (i, lhs) in hails.iter().enumerate() {
+        for (j, rhs) in hails.iter().enumerate().skip(i + 1) {
+            let crossing = lhs.crossing(rhs, area.dims());
+            let inside = match &crossing {
+                Crossing::Future(at) | Crossing::Past(at) => area.contains(&at.pos),
+                Crossing::Coincident => area.is_crossed_by(lhs),
+                Crossing::Parallel | Crossing::Skew => false,
+            };
+            if inside {
+                ans.push((i, j, crossing));
             }
         }
     }
     ans
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Fraction {
+    num: i128,
+    den: i128,
+}
+
+impl Fraction {
+    fn new(num: i128, den: i128) -> Self {
+        if den == 0 {
+            panic!("zero denominator: {}/{}", num, den);
+        }
+        let sign = if den < 0 { -1 } else { 1 };
+        let (num, den) = (num * sign, den * sign);
+        if num == 0 {
+            return Self { num: 0, den: 1 };
+        }
+        let d = gcd(num, den);
+        Self {
+            num: num / d,
+            den: den / d,
+        }
+    }
+
+    fn num(&self) -> i128 {
+        self.num
+    }
+
+    fn den(&self) -> i128 {
+        self.den
+    }
+
+    fn is_negative(&self) -> bool {
+        self.num < 0
+    }
+}
+
+impl From<i128> for Fraction {
+    fn from(value: i128) -> Self {
+        Self { num: value, den: 1 }
+    }
+}
+
+impl PartialOrd for Fraction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Fraction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // denominators are always positive
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl std::fmt::Display for Fraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
 fn gcd(a: i128, b: i128) -> i128 {
     let a = a.abs();
     let b = b.abs();
@@ -170,6 +338,15 @@ struct Vec3D {
 }
 
 impl Vec3D {
+    fn axis(&self, axis: usize) -> i128 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            2 => self.z,
+            _ => unreachable!(),
+        }
+    }
+
     fn is_parallel(&self, rhs: &Self) -> bool {
         self.normalized() == rhs.normalized()
     }
@@ -318,39 +495,74 @@ impl Hail {
             None
         }
     }
-    fn collides(&self, rhs: &Self) -> Option<(f64, f64)> {
+    fn crossing(&self, rhs: &Self, dims: usize) -> Crossing {
         let Hail { pos: p0, vel: v0 } = self;
         let Hail { pos: p1, vel: v1 } = rhs;
 
         // p0 + v0 * t0 = p1 + v1 * t1
-        // v0 * t0 - v1 * t1 = p1 - p0;
-        // v0x -v1x t0 = p1x - p0x
-        // v0y -v1y t1 = p1y - p0y
+        // v0 * t0 - v1 * t1 = p1 - p0
+        // solved on the first pair of axes that is not degenerate
+        let pairs: &[(usize, usize)] = if dims == 2 {
+            &[(0, 1)]
+        } else {
+            &[(0, 1), (0, 2), (1, 2)]
+        };
+        let solved = pairs.iter().find_map(|&(i, j)| {
+            let a = v0.axis(i);
+            let b = -v1.axis(i);
+            let c = v0.axis(j);
+            let d = -v1.axis(j);
+            let e = p1.axis(i) - p0.axis(i);
+            let f = p1.axis(j) - p0.axis(j);
+            let det = a * d - b * c;
+            if det == 0 {
+                None
+            } else {
+                Some((
+                    Fraction::new(d * e - b * f, det),
+                    Fraction::new(a * f - c * e, det),
+                ))
+            }
+        });
+
+        let Some((t0, t1)) = solved else {
+            // velocities are parallel: same line iff p1 - p0 runs along a moving one too;
+            // two hailstones standing still only coincide at the same point
+            let dp = *p1 - *p0;
+            let moving = [v0, v1]
+                .into_iter()
+                .find(|v| (0..dims).any(|axis| v.axis(axis) != 0));
+            let coincident = match moving {
+                Some(dir) => (0..dims).all(|i| {
+                    (0..dims).all(|j| dp.axis(i) * dir.axis(j) == dp.axis(j) * dir.axis(i))
+                }),
+                None => (0..dims).all(|axis| dp.axis(axis) == 0),
+            };
+            return if coincident {
+                Crossing::Coincident
+            } else {
+                Crossing::Parallel
+            };
+        };
 
-        let a = v0.x as f64;
-        let b = -v1.x as f64;
-        let c = v0.y as f64;
-        let d = -v1.y as f64;
-        let e = p1.x as f64 - p0.x as f64;
-        let f = p1.y as f64 - p0.y as f64;
+        let pos: Vec<Fraction> = (0..dims).map(|axis| self.at_axis(axis, t0)).collect();
+        if (0..dims).any(|axis| rhs.at_axis(axis, t1) != pos[axis]) {
+            return Crossing::Skew;
+        }
 
-        let det = a * d - b * c;
-        if det == 0.0 {
-            // cannot solve
-            None
+        let intersection = Intersection { t0, t1, pos };
+        if t0.is_negative() || t1.is_negative() {
+            Crossing::Past(intersection)
         } else {
-            let (a, b, c, d) = (d / det, -b / det, -c / det, a / det);
-            let t0 = a * e + b * f;
-            let t1 = c * e + d * f;
-            Some((t0, t1))
+            Crossing::Future(intersection)
         }
     }
 
-    fn at(&self, t: f64) -> (f64, f64, f64) {
-        let x = (self.pos.x as f64) + (self.vel.x as f64) * t;
-        let y = (self.pos.y as f64) + (self.vel.y as f64) * t;
-        let z = (self.pos.z as f64) + (self.vel.z as f64) * t;
-        (x, y, z)
+    fn at_axis(&self, axis: usize, t: Fraction) -> Fraction {
+        Fraction::new(
+            self.pos.axis(axis) * t.den() + self.vel.axis(axis) * t.num(),
+            t.den(),
+        )
     }
 
     fn t(&self, pos: Vec3D) -> i128 {
@@ -382,7 +594,7 @@ mod day24_tests {
     #[test]
     fn test_part_1() {
         let input = Day24::test_input();
-        let ans = part_1(input, 7.0, 27.0);
+        let ans = part_1(input, 7, 27);
         assert_eq!(ans, 2);
     }
 
@@ -407,4 +619,64 @@ mod day24_tests {
             }
         )
     }
+
+    #[test]
+    fn test_crossings_in() {
+        let hails = parse_hails(&Day24::test_input());
+        let crossings = crossings_in(&hails, &TestArea::square(7, 27));
+        // two in the future, three in the past
+        assert_eq!(crossings.len(), 5);
+
+        // hailstones A and B cross at x=14.333, y=15.333
+        assert_eq!(
+            crossings[0],
+            (
+                0,
+                1,
+                Crossing::Future(Intersection {
+                    t0: Fraction::new(7, 3),
+                    t1: Fraction::new(11, 3),
+                    pos: vec![Fraction::new(43, 3), Fraction::new(46, 3)],
+                })
+            )
+        );
+        // hailstones A and E crossed in the past for A
+        assert!(matches!(crossings[2], (0, 4, Crossing::Past(_))));
+    }
+
+    #[test]
+    fn test_crossing_kinds() {
+        let hails = parse_hails(&Day24::test_input());
+        assert_eq!(hails[1].crossing(&hails[2], 2), Crossing::Parallel);
+
+        let lhs: Hail = "0, 0, 0 @ 1, 1, 1".parse().unwrap();
+        let rhs: Hail = "2, 2, 2 @ -2, -2, -2".parse().unwrap();
+        assert_eq!(lhs.crossing(&rhs, 3), Crossing::Coincident);
+
+        let rhs: Hail = "1, 0, 5 @ 0, 1, 0".parse().unwrap();
+        assert_eq!(lhs.crossing(&rhs, 3), Crossing::Skew);
+        assert!(matches!(lhs.crossing(&rhs, 2), Crossing::Future(_)));
+
+        let crossings = crossings_in(&[lhs, rhs], &TestArea::cube(0, 10));
+        assert!(crossings.is_empty());
+
+        // a hailstone standing still only shares a path with one through its spot
+        let still: Hail = "0, 0, 0 @ 0, 0, 0".parse().unwrap();
+        let rhs: Hail = "5, 5, 5 @ 1, 0, 0".parse().unwrap();
+        assert_eq!(still.crossing(&rhs, 3), Crossing::Parallel);
+        assert_eq!(rhs.crossing(&still, 3), Crossing::Parallel);
+        let rhs: Hail = "5, 5, 5 @ -1, -1, -1".parse().unwrap();
+        assert_eq!(still.crossing(&rhs, 3), Crossing::Coincident);
+        let other: Hail = "0, 0, 1 @ 0, 0, 0".parse().unwrap();
+        assert_eq!(still.crossing(&other, 3), Crossing::Parallel);
+        assert_eq!(still.crossing(&still, 3), Crossing::Coincident);
+    }
+
+    #[test]
+    fn test_fraction() {
+        assert_eq!(Fraction::new(4, -6), Fraction::new(-2, 3));
+        assert!(Fraction::new(1, 3) < Fraction::new(1, 2));
+        assert_eq!(Fraction::new(0, -5), Fraction::from(0));
+        assert_eq!(Fraction::new(-7, 3).to_string(), "-7/3");
+    }
 }
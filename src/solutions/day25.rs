@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
 use super::Solution;
 
@@ -48,6 +48,26 @@ impl Node {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cut {
+    weight: usize,
+    left: HashSet<String>,
+    right: HashSet<String>,
+    edges: Vec<(String, String, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Partition {
+    parts: Vec<HashSet<String>>,
+    edges: Vec<(String, String, usize)>,
+}
+
+impl Partition {
+    fn weight(&self) -> usize {
+        self.edges.iter().map(|(_, _, cost)| cost).sum()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 struct Graph {
     nodes: HashMap<String, Node>,
@@ -160,41 +180,133 @@ impl Graph {
         (ans, last)
     }
 
-    // minimum_cut, partition_size
-    fn minimum_cut(&self, node_name: &str) -> (usize, usize) {
+    fn minimum_cut(&self, node_name: &str) -> Option<Cut> {
+        if self.nodes.len() < 2 {
+            return None;
+        }
         let mut graph = self.clone();
-        // min cut, partition size at that time
-        let mut min_cut = (usize::MAX, 0);
-        let mut order: HashMap<String, usize> =
-            self.nodes.keys().map(|key| (key.clone(), 1)).collect();
-        let size = self.nodes.len();
+        // min cut, the nodes merged into `t` at that time
+        let mut min_cut: (usize, Vec<String>) = (usize::MAX, vec![]);
+        let mut members: HashMap<String, Vec<String>> = self
+            .nodes
+            .keys()
+            .map(|key| (key.clone(), vec![key.clone()]))
+            .collect();
 
         while graph.nodes.len() > 1 {
-            if graph.nodes.len() % (size / 10) == 0 {
-                println!("{}%", 100 * graph.nodes.len() / size)
+            let (curr, (s, t)) = graph.min_cut_phase(node_name);
+
+            let deleted = members.remove(&t).unwrap();
+
+            if curr < min_cut.0 {
+                min_cut = (curr, deleted.clone());
             }
 
-            let (curr, (s, t)) = graph.min_cut_phase(&node_name);
+            members.get_mut(&s).unwrap().extend(deleted);
+        }
+
+        let (weight, left) = min_cut;
+        let left: HashSet<String> = left.into_iter().collect();
+        let right: HashSet<String> = self
+            .nodes
+            .keys()
+            .filter(|name| !left.contains(*name))
+            .cloned()
+            .collect();
+        let edges = self.cut_edges(&[&left, &right]);
+        Some(Cut {
+            weight,
+            left,
+            right,
+            edges,
+        })
+    }
+
+    // edges whose two ends lie in different parts, each listed once
+    fn cut_edges(&self, parts: &[&HashSet<String>]) -> Vec<(String, String, usize)> {
+        let part_of: HashMap<&String, usize> = parts
+            .iter()
+            .enumerate()
+            .flat_map(|(i, part)| part.iter().map(move |name| (name, i)))
+            .collect();
+        let mut edges: Vec<(String, String, usize)> = self
+            .nodes
+            .values()
+            .flat_map(|node| {
+                node.edges
+                    .iter()
+                    .filter(|(name, _)| {
+                        node.name < *name && part_of.get(&node.name) != part_of.get(name)
+                    })
+                    .map(|(name, cost)| (node.name.clone(), name.clone(), *cost))
+            })
+            .collect();
+        edges.sort();
+        edges
+    }
 
-            let deleted = order.remove(&t).unwrap();
+    fn subgraph(&self, names: &HashSet<String>) -> Self {
+        let nodes = self
+            .nodes
+            .values()
+            .filter(|node| names.contains(&node.name))
+            .map(|node| Node {
+                name: node.name.clone(),
+                edges: node
+                    .edges
+                    .iter()
+                    .filter(|(name, _)| names.contains(name))
+                    .cloned()
+                    .collect(),
+            })
+            .map(|node| (node.name.clone(), node))
+            .collect();
+        Self { nodes }
+    }
 
-            *order.get_mut(&s).unwrap() += deleted;
+    // recursive bisection: keep splitting the part with the lightest minimum cut.
+    // greedy, so not always the optimal k-cut, but exact for k = 2.
+    fn k_way_partition(&self, k: usize) -> Option<Partition> {
+        if k == 0 || k > self.nodes.len() {
+            return None;
+        }
+        let all: HashSet<String> = self.nodes.keys().cloned().collect();
+        let mut parts: Vec<(HashSet<String>, Option<Cut>)> = vec![];
+        let mut queue = vec![all];
 
-            if curr < min_cut.0 {
-                min_cut = (curr, deleted);
+        loop {
+            for part in queue.drain(..) {
+                let graph = self.subgraph(&part);
+                let cut = graph
+                    .nodes
+                    .keys()
+                    .next()
+                    .and_then(|pivot| graph.minimum_cut(pivot));
+                parts.push((part, cut));
+            }
+            if parts.len() == k {
+                break;
             }
+            let (lightest, _) = parts
+                .iter()
+                .enumerate()
+                .filter_map(|(i, (_, cut))| cut.as_ref().map(|cut| (i, cut.weight)))
+                .min_by_key(|(_, weight)| *weight)?;
+            let (_, cut) = parts.remove(lightest);
+            let cut = cut.unwrap();
+            queue.push(cut.left);
+            queue.push(cut.right);
         }
-        min_cut
+
+        let parts: Vec<HashSet<String>> = parts.into_iter().map(|(part, _)| part).collect();
+        let edges = self.cut_edges(&parts.iter().collect::<Vec<_>>());
+        Some(Partition { parts, edges })
     }
+
     fn part_1(&self) -> usize {
         // if min cut is 3: multiply the sizes of the two partition
-        let pivot = self.nodes.keys().next().unwrap();
-
-        let (min_cut, node_order) = self.minimum_cut(pivot);
-        println!("min_cut was: {}", min_cut);
-        println!("pivot was: {}", pivot);
-        let n = self.nodes.len();
-        (n - node_order) * node_order
+        let cut = IndexedGraph::from(self).minimum_cut().unwrap();
+        cut.left.len() * cut.right.len()
     }
     fn from(str: &str) -> Self {
         let mut nodes: HashMap<String, Node> = HashMap::new();
@@ -202,7 +314,16 @@ impl Graph {
             let line = line.trim();
             let mut words = line.split(":");
             let me = words.next().unwrap().trim();
-            let neighbors: Vec<&str> = words.next().unwrap().trim().split_whitespace().collect();
+            // "name=weight", or just "name" for a weight of 1
+            let neighbors: Vec<(&str, usize)> = words
+                .next()
+                .unwrap()
+                .split_whitespace()
+                .map(|word| match word.split_once('=') {
+                    Some((name, weight)) => (name, weight.parse().unwrap()),
+                    None => (word, 1),
+                })
+                .collect();
             let node = if let Some(n) = nodes.get_mut(me) {
                 n
             } else {
@@ -210,18 +331,18 @@ impl Graph {
                 nodes.get_mut(me).unwrap()
             };
 
-            for neighbor in neighbors.iter() {
-                node.edges.push((neighbor.to_string(), 1));
+            for (neighbor, weight) in neighbors.iter() {
+                node.edges.push((neighbor.to_string(), *weight));
             }
-            for neighbor in neighbors {
+            for (neighbor, weight) in neighbors {
                 if let Some(n) = nodes.get_mut(neighbor) {
-                    n.edges.push((me.to_string(), 1));
+                    n.edges.push((me.to_string(), weight));
                 } else {
                     nodes.insert(
                         neighbor.to_string(),
                         Node {
                             name: neighbor.to_string(),
-                            edges: vec![(me.to_string(), 1)],
+                            edges: vec![(me.to_string(), weight)],
                         },
                     );
                 }
@@ -230,6 +351,198 @@ impl Graph {
         Self { nodes }
     }
 }
+// the same graph with node names interned to indices
+#[derive(Debug, Clone, PartialEq)]
+struct IndexedGraph {
+    names: Vec<String>,
+    adj: Vec<HashMap<usize, usize>>,
+}
+
+impl IndexedGraph {
+    fn from(graph: &Graph) -> Self {
+        let mut names: Vec<String> = graph.nodes.keys().cloned().collect();
+        names.sort();
+        let ids: HashMap<&String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name, id))
+            .collect();
+        let mut adj = vec![HashMap::new(); names.len()];
+        for node in graph.nodes.values() {
+            let u = ids[&node.name];
+            for (name, cost) in node.edges.iter() {
+                *adj[u].entry(ids[name]).or_insert(0) += cost;
+            }
+        }
+        Self { names, adj }
+    }
+
+    // maximum adjacency ordering with a lazy max-heap.
+    // returns the cut of the phase and the last two nodes added.
+    fn min_cut_phase(adj: &[HashMap<usize, usize>], alive: &[usize]) -> (usize, (usize, usize)) {
+        let mut tightness = vec![0; adj.len()];
+        let mut added = vec![false; adj.len()];
+        let mut last = (alive[0], alive[0]);
+
+        // restart from an untouched node if the graph is disconnected
+        for &root in alive.iter() {
+            if added[root] {
+                continue;
+            }
+            let mut heap = BinaryHeap::from([(0, root)]);
+            while let Some((weight, v)) = heap.pop() {
+                if added[v] || weight != tightness[v] {
+                    continue;
+                }
+                added[v] = true;
+                last = (last.1, v);
+                for (&u, &cost) in adj[v].iter() {
+                    if !added[u] {
+                        tightness[u] += cost;
+                        heap.push((tightness[u], u));
+                    }
+                }
+            }
+        }
+
+        (tightness[last.1], last)
+    }
+
+    fn minimum_cut(&self) -> Option<Cut> {
+        let n = self.names.len();
+        if n < 2 {
+            return None;
+        }
+        let mut adj = self.adj.clone();
+        let mut members: Vec<Vec<usize>> = (0..n).map(|v| vec![v]).collect();
+        let mut alive: Vec<usize> = (0..n).collect();
+        let mut min_cut: (usize, Vec<usize>) = (usize::MAX, vec![]);
+
+        while alive.len() > 1 {
+            let (curr, (s, t)) = Self::min_cut_phase(&adj, &alive);
+            if curr < min_cut.0 {
+                min_cut = (curr, members[t].clone());
+            }
+
+            // merge t into s
+            for (v, cost) in std::mem::take(&mut adj[t]) {
+                adj[v].remove(&t);
+                if v != s {
+                    *adj[v].entry(s).or_insert(0) += cost;
+                    *adj[s].entry(v).or_insert(0) += cost;
+                }
+            }
+            let deleted = std::mem::take(&mut members[t]);
+            members[s].extend(deleted);
+            alive.retain(|&v| v != t);
+        }
+
+        Some(self.cut(min_cut.0, &min_cut.1))
+    }
+
+    // when the cut size is known to be small (3 for the puzzle):
+    // count edge-disjoint paths from node 0 to every other node.
+    // the first sink that cannot be reached by more than `k` paths is on the other side
+    // of a cut of weight at most `k`, and the residual graph tells which side is which.
+    fn cut_at_most(&self, k: usize) -> Option<Cut> {
+        let n = self.names.len();
+        // edge e and e ^ 1 are the two directions of the same undirected edge
+        let mut to: Vec<usize> = vec![];
+        let mut capacity: Vec<usize> = vec![];
+        let mut out: Vec<Vec<usize>> = vec![vec![]; n];
+        for u in 0..n {
+            for (&v, &cost) in self.adj[u].iter().filter(|(&v, _)| u < v) {
+                out[u].push(to.len());
+                to.push(v);
+                capacity.push(cost);
+                out[v].push(to.len());
+                to.push(u);
+                capacity.push(cost);
+            }
+        }
+
+        for sink in 1..n {
+            let mut residual = capacity.clone();
+            let mut flow = 0;
+            loop {
+                // bfs for an augmenting path
+                let mut from_edge: Vec<Option<usize>> = vec![None; n];
+                let mut seen = vec![false; n];
+                seen[0] = true;
+                let mut queue = VecDeque::from([0]);
+                while let Some(u) = queue.pop_front() {
+                    for &e in out[u].iter() {
+                        let v = to[e];
+                        if !seen[v] && residual[e] > 0 {
+                            seen[v] = true;
+                            from_edge[v] = Some(e);
+                            queue.push_back(v);
+                        }
+                    }
+                }
+
+                if !seen[sink] {
+                    let left: Vec<usize> = (0..n).filter(|&v| seen[v]).collect();
+                    return Some(self.cut(flow, &left));
+                }
+
+                let mut path = vec![];
+                let mut v = sink;
+                while let Some(e) = from_edge[v] {
+                    path.push(e);
+                    v = to[e ^ 1];
+                }
+                let bottleneck = path.iter().map(|&e| residual[e]).min().unwrap();
+                for e in path {
+                    residual[e] -= bottleneck;
+                    residual[e ^ 1] += bottleneck;
+                }
+                flow += bottleneck;
+                if flow > k {
+                    break;
+                }
+            }
+        }
+        None
+    }
+
+    fn cut(&self, weight: usize, left: &[usize]) -> Cut {
+        let mut is_left = vec![false; self.names.len()];
+        for &v in left.iter() {
+            is_left[v] = true;
+        }
+        let mut edges: Vec<(String, String, usize)> = left
+            .iter()
+            .flat_map(|&u| {
+                self.adj[u]
+                    .iter()
+                    .filter(|(&v, _)| !is_left[v])
+                    .map(move |(&v, &cost)| {
+                        let (a, b) = (&self.names[u], &self.names[v]);
+                        if a < b {
+                            (a.clone(), b.clone(), cost)
+                        } else {
+                            (b.clone(), a.clone(), cost)
+                        }
+                    })
+            })
+            .collect();
+        edges.sort();
+        let names = |side: bool| -> HashSet<String> {
+            (0..self.names.len())
+                .filter(|&v| is_left[v] == side)
+                .map(|v| self.names[v].clone())
+                .collect()
+        };
+        Cut {
+            weight,
+            left: names(true),
+            right: names(false),
+            edges,
+        }
+    }
+}
+
 #[cfg(test)]
 mod day25_tests {
     use super::*;
@@ -321,4 +634,134 @@ mod day25_tests {
         };
         assert_eq!(graph.part_1(), 3)
     }
+
+    #[test]
+    fn test_cut_edges() {
+        let graph = Graph::from(&Day25::test_input());
+        let cut = graph.minimum_cut("jqt").unwrap();
+        assert_eq!(cut.weight, 3);
+        assert_eq!(
+            cut.edges,
+            vec![
+                ("bvb".to_string(), "cmg".to_string(), 1),
+                ("hfx".to_string(), "pzl".to_string(), 1),
+                ("jqt".to_string(), "nvd".to_string(), 1),
+            ]
+        );
+        let mut sizes = [cut.left.len(), cut.right.len()];
+        sizes.sort();
+        assert_eq!(sizes, [6, 9]);
+    }
+
+    #[test]
+    fn test_weighted_input() {
+        let input = "a: b=5 c=5
+        b: c=5
+        c: d
+        d: e=5 f=5
+        e: f=5";
+        let graph = Graph::from(input);
+        let cut = graph.minimum_cut("a").unwrap();
+        assert_eq!(cut.weight, 1);
+        assert_eq!(cut.edges, vec![("c".to_string(), "d".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_k_way_partition() {
+        let input = "a: b=5 c=5
+        b: c=5
+        c: d=2
+        d: e=5 f=5
+        e: f=5 g
+        g: h=5 i=5
+        h: i=5";
+        let graph = Graph::from(input);
+        let partition = graph.k_way_partition(3).unwrap();
+        assert_eq!(partition.weight(), 3);
+        let mut sizes: Vec<usize> = partition.parts.iter().map(|part| part.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![3, 3, 3]);
+
+        assert_eq!(graph.k_way_partition(1).unwrap().weight(), 0);
+        assert!(graph.k_way_partition(10).is_none());
+    }
+
+    // two dense clusters of `size` nodes joined by `bridges` edges
+    fn two_clusters(size: usize, degree: usize, bridges: usize) -> Graph {
+        let mut seed: u64 = 2023;
+        let mut random = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        let mut lines = vec![];
+        for cluster in 0..2 {
+            for i in 0..size {
+                // a ring keeps each cluster connected
+                let mut neighbors = vec![(i + 1) % size];
+                for _ in 1..degree {
+                    neighbors.push(random(size));
+                }
+                let neighbors: Vec<String> = neighbors
+                    .into_iter()
+                    .filter(|&j| j != i)
+                    .map(|j| format!("c{}n{}", cluster, j))
+                    .collect();
+                lines.push(format!("c{}n{}: {}", cluster, i, neighbors.join(" ")));
+            }
+        }
+        for b in 0..bridges {
+            lines.push(format!("c0n{}: c1n{}", b, b));
+        }
+        Graph::from(&lines.join("\n"))
+    }
+
+    #[test]
+    fn test_indexed_graph() {
+        let graph = Graph::from(&Day25::test_input());
+        let indexed = IndexedGraph::from(&graph);
+        let expected = graph.minimum_cut("jqt").unwrap();
+        let cut = indexed.minimum_cut().unwrap();
+        assert_eq!(cut.weight, expected.weight);
+        assert_eq!(cut.edges, expected.edges);
+
+        let by_flow = indexed.cut_at_most(3).unwrap();
+        assert_eq!(by_flow.weight, 3);
+        assert_eq!(by_flow.edges, expected.edges);
+        assert!(indexed.cut_at_most(2).is_none());
+    }
+
+    #[test]
+    fn test_methods_agree() {
+        let graph = two_clusters(40, 5, 3);
+        let slow = graph.minimum_cut("c0n0").unwrap();
+        let indexed = IndexedGraph::from(&graph);
+        assert_eq!(slow.weight, 3);
+        assert_eq!(indexed.minimum_cut().unwrap().edges, slow.edges);
+        assert_eq!(indexed.cut_at_most(3).unwrap().edges, slow.edges);
+    }
+
+    // cargo test --release bench_min_cut -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_min_cut() {
+        let graph = two_clusters(200, 5, 3);
+        let indexed = IndexedGraph::from(&graph);
+
+        let now = std::time::Instant::now();
+        let slow = graph.minimum_cut("c0n0").unwrap();
+        println!("stoer-wagner on strings: {} ms", now.elapsed().as_millis());
+
+        let now = std::time::Instant::now();
+        let fast = indexed.minimum_cut().unwrap();
+        println!("stoer-wagner with a heap: {} ms", now.elapsed().as_millis());
+
+        let now = std::time::Instant::now();
+        let flow = indexed.cut_at_most(3).unwrap();
+        println!("edge-disjoint paths: {} ms", now.elapsed().as_millis());
+
+        assert_eq!(slow.edges, fast.edges);
+        assert_eq!(slow.edges, flow.edges);
+    }
 }